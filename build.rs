@@ -15,6 +15,36 @@ enum Command {
     Low,
     High,
     Both,
+    Obstacle,
+    Heart(Track),
+    MusicNote(Track),
+    Ghost(Track),
+    GhostBoth,
+    BossEnter,
+    BossExit,
+    Throw(Track),
+}
+
+enum Track {
+    Low,
+    High,
+}
+
+impl Track {
+    fn from_str(str: &str) -> Result<Self, FragmentError> {
+        match str {
+            "L" => Ok(Track::Low),
+            "H" => Ok(Track::High),
+            _ => Err(FragmentError::UnknownCommand),
+        }
+    }
+
+    fn to_ingame_track(&self) -> &'static str {
+        match self {
+            Track::Low => "Track::Low",
+            Track::High => "Track::High",
+        }
+    }
 }
 
 impl Command {
@@ -23,34 +53,218 @@ impl Command {
             "L" => Ok(Command::Low),
             "H" => Ok(Command::High),
             "B" => Ok(Command::Both),
-            _ => Err(FragmentError::UnknownCommand),
+            "O" => Ok(Command::Obstacle),
+            "~B" => Ok(Command::GhostBoth),
+            "BE" => Ok(Command::BossEnter),
+            "BX" => Ok(Command::BossExit),
+            _ => {
+                // Pickups are a prefix followed by the track
+                if let Some(track) = str.strip_prefix('+') {
+                    Ok(Command::Heart(Track::from_str(track)?))
+                } else if let Some(track) = str.strip_prefix('*') {
+                    Ok(Command::MusicNote(Track::from_str(track)?))
+                } else if let Some(track) = str.strip_prefix('~') {
+                    Ok(Command::Ghost(Track::from_str(track)?))
+                } else if let Some(track) = str.strip_prefix('T') {
+                    Ok(Command::Throw(Track::from_str(track)?))
+                } else {
+                    Err(FragmentError::UnknownCommand)
+                }
+            }
+        }
+    }
+
+    /// Whether this command spawns something that travels to the player, rather
+    /// than running when it's due
+    fn travels(&self) -> bool {
+        !matches!(self, Command::BossEnter | Command::BossExit)
+    }
+
+    /// Number of hittable notes spawned by this command
+    fn notes(&self) -> usize {
+        match self {
+            Command::Low | Command::High | Command::Ghost(_) | Command::Throw(_) => 1,
+            Command::Both | Command::GhostBoth => 2,
+            Command::Obstacle
+            | Command::Heart(_)
+            | Command::MusicNote(_)
+            | Command::BossEnter
+            | Command::BossExit => 0,
         }
     }
 
     fn to_ingame_command(&self) -> String {
         match self {
-            Command::Low => "Command::Note(Track::Low)",
-            Command::High => "Command::Note(Track::High)",
-            Command::Both => "Command::NoteBoth",
+            Command::Low => "Command::Note(Track::Low)".to_string(),
+            Command::High => "Command::Note(Track::High)".to_string(),
+            Command::Both => "Command::NoteBoth".to_string(),
+            Command::Obstacle => "Command::Obstacle".to_string(),
+            Command::Heart(track) => format!("Command::Heart({})", track.to_ingame_track()),
+            Command::MusicNote(track) => {
+                format!("Command::MusicNote({})", track.to_ingame_track())
+            }
+            Command::Ghost(track) => format!("Command::Ghost({})", track.to_ingame_track()),
+            Command::GhostBoth => "Command::GhostBoth".to_string(),
+            Command::BossEnter => "Command::BossEnter".to_string(),
+            Command::BossExit => "Command::BossExit".to_string(),
+            Command::Throw(track) => format!("Command::Throw({})", track.to_ingame_track()),
+        }
+    }
+}
+
+// Frames a note takes to reach the judgement area at normal speed, this must
+// match the distance it covers in game at one pixel per frame
+const NOTE_TRAVEL_FRAMES: f32 = 194.0;
+
+enum MotionType {
+    Constant,
+    Accelerate,
+    Decelerate,
+    Wave,
+}
+
+impl MotionType {
+    fn to_ingame_motion_type(&self) -> &'static str {
+        match self {
+            MotionType::Constant => "MotionType::Constant",
+            MotionType::Accelerate => "MotionType::Accelerate",
+            MotionType::Decelerate => "MotionType::Decelerate",
+            MotionType::Wave => "MotionType::Wave",
+        }
+    }
+}
+
+// The mixer runs at this rate, and include_wav doesn't resample
+const SAMPLE_RATE: u32 = 32768;
+
+// Songs can replace their theme with their own background, in the same
+// layout as the theme images
+const BACKGROUND_FILES: [&str; 2] = ["background.aseprite", "background.png"];
+const BACKGROUND_WIDTH: u32 = 256;
+const BACKGROUND_HEIGHT: u32 = 232;
+
+// Shared tiles converted alongside a song's background, so they use its palettes
+const BACKGROUND_TILES: &str = "assets/background.aseprite";
+
+// Note skins are tags in this file, each with a matching `_hit` tag
+const NOTE_SPRITES: &str = "assets/note.aseprite";
+const DEFAULT_SKIN: &str = "note";
+
+// Background themes songs can pick in their metadata
+enum Theme {
+    Standard,
+    Night,
+    Sunset,
+}
+
+impl Theme {
+    fn from_str(str: &str) -> Result<Self, MetadataError> {
+        match str {
+            "standard" => Ok(Theme::Standard),
+            "night" => Ok(Theme::Night),
+            "sunset" => Ok(Theme::Sunset),
+            _ => Err(MetadataError::UnknownTheme),
+        }
+    }
+
+    fn to_ingame_theme(&self) -> &'static str {
+        match self {
+            Theme::Standard => "Theme::Standard",
+            Theme::Night => "Theme::Night",
+            Theme::Sunset => "Theme::Sunset",
         }
-        .to_string()
     }
 }
 
 struct Fragment {
     command: Command,
     delay: f32,
+    speed: f32,
+    motion: MotionType,
+    skin: Option<String>,
+    sample: Option<String>, // Keysound played when the note is hit
+}
+
+impl Fragment {
+    /// Frames the note takes to reach the judgement area
+    fn travel(&self) -> f32 {
+        (NOTE_TRAVEL_FRAMES / self.speed).round()
+    }
+
+    /// Frame of the song the fragment runs on, relative to the music starting.
+    /// Notes run early so they arrive on time, and slower notes earlier still,
+    /// negative if they have to spawn before the music starts
+    fn frame(&self) -> i32 {
+        let mut frame = (self.delay * 60.0) / 1000.0;
+
+        if self.command.travels() {
+            frame -= 146.0 + (self.travel() - NOTE_TRAVEL_FRAMES);
+        }
+
+        frame.round() as i32
+    }
 }
 
 struct Map {
+    song_dir: PathBuf,
     song_file: PathBuf,
     fragments: Vec<Fragment>,
+    theme: Theme,
+    background: Option<PathBuf>,
 }
 
+#[derive(Debug)]
+enum MetadataError {
+    InvalidLine,
+    UnknownKey,
+    UnknownTheme,
+}
+
+impl Display for MetadataError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidLine => write!(f, "Invalid metadata line, expected key=value"),
+            Self::UnknownKey => write!(f, "Unknown metadata key"),
+            Self::UnknownTheme => write!(f, "Unknown theme"),
+        }
+    }
+}
+
+impl Error for MetadataError {}
+
+#[derive(Debug)]
+enum BackgroundError {
+    UnknownFormat,
+    InvalidSize(u32, u32),
+}
+
+impl Display for BackgroundError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownFormat => write!(f, "Background must be a png or aseprite file"),
+            Self::InvalidSize(width, height) => write!(
+                f,
+                "Background is {}x{}, it must be {}x{}",
+                width, height, BACKGROUND_WIDTH, BACKGROUND_HEIGHT
+            ),
+        }
+    }
+}
+
+impl Error for BackgroundError {}
+
 #[derive(Debug)]
 enum FragmentError {
     InvalidLine,
     UnknownCommand,
+    UnknownModifier,
+    InvalidSpeed,
+    UnknownSkin(String),
+    SkinWithoutNote,
+    SkinOnGhost,
+    MissingSample,
+    InvalidSample,
+    SampleWithoutNote,
 }
 
 impl Display for FragmentError {
@@ -58,43 +272,289 @@ impl Display for FragmentError {
         match self {
             Self::InvalidLine => write!(f, "Invalid line"),
             Self::UnknownCommand => write!(f, "Unknown command"),
+            Self::UnknownModifier => write!(f, "Unknown modifier"),
+            Self::InvalidSpeed => write!(f, "Speed must be a finite, positive number"),
+            Self::UnknownSkin(name) => write!(
+                f,
+                "Unknown skin `{}`, it needs a `{}` tag and a `{}_hit` tag in {}",
+                name, name, name, NOTE_SPRITES
+            ),
+            Self::SkinWithoutNote => write!(f, "Skins can only be used on notes"),
+            Self::SkinOnGhost => write!(f, "Skins can't be used on ghost notes"),
+            Self::MissingSample => write!(f, "Sample not found in the song directory"),
+            Self::InvalidSample => write!(f, "Samples must be {}Hz wav files", SAMPLE_RATE),
+            Self::SampleWithoutNote => write!(f, "Samples can only be used on notes"),
         }
     }
 }
 
 impl Error for FragmentError {}
 
-fn read_fragments(path: &Path) -> io::Result<Vec<Fragment>> {
+/// Reads the names of the tags in an aseprite file
+fn read_tags(path: &Path) -> io::Result<Vec<String>> {
+    fn u16_at(data: &[u8], offset: usize) -> usize {
+        u16::from_le_bytes([data[offset], data[offset + 1]]) as usize
+    }
+
+    fn u32_at(data: &[u8], offset: usize) -> usize {
+        u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap()) as usize
+    }
+
+    const HEADER_SIZE: usize = 128;
+    const FRAME_HEADER_SIZE: usize = 16;
+    const TAGS_CHUNK: usize = 0x2018;
+
+    let data = fs::read(path)?;
+    let frames = u16_at(&data, 6);
+    let mut tags = Vec::new();
+    let mut frame_start = HEADER_SIZE;
+
+    for _ in 0..frames {
+        let frame_size = u32_at(&data, frame_start);
+        let chunks = match u32_at(&data, frame_start + 12) {
+            0 => u16_at(&data, frame_start + 6),
+            chunks => chunks,
+        };
+
+        let mut chunk_start = frame_start + FRAME_HEADER_SIZE;
+
+        for _ in 0..chunks {
+            let chunk_size = u32_at(&data, chunk_start);
+
+            if u16_at(&data, chunk_start + 4) == TAGS_CHUNK {
+                let mut offset = chunk_start + 6;
+                let count = u16_at(&data, offset);
+                offset += 10;
+
+                for _ in 0..count {
+                    // Skip the frame range, direction, repeat and colour
+                    offset += 17;
+
+                    let length = u16_at(&data, offset);
+                    offset += 2;
+
+                    tags.push(String::from_utf8_lossy(&data[offset..offset + length]).into_owned());
+                    offset += length;
+                }
+            }
+
+            chunk_start += chunk_size;
+        }
+
+        frame_start += frame_size;
+    }
+
+    Ok(tags)
+}
+
+/// Reads the sample rate from a wav file's format chunk
+fn read_sample_rate(path: &Path) -> io::Result<u32> {
+    let data = fs::read(path)?;
+
+    if data.len() < 12 || &data[0..4] != b"RIFF" || &data[8..12] != b"WAVE" {
+        return Err(io::Error::other(FragmentError::InvalidSample));
+    }
+
+    let mut offset = 12;
+
+    while offset + 8 <= data.len() {
+        let size = u32::from_le_bytes(data[offset + 4..offset + 8].try_into().unwrap()) as usize;
+
+        if &data[offset..offset + 4] == b"fmt " && offset + 16 <= data.len() {
+            return Ok(u32::from_le_bytes(
+                data[offset + 12..offset + 16].try_into().unwrap(),
+            ));
+        }
+
+        // Chunks are padded to an even size
+        offset += 8 + size + size % 2;
+    }
+
+    Err(io::Error::other(FragmentError::InvalidSample))
+}
+
+/// Reads a line of the chart, with its delay, command and modifiers
+fn read_fragment(line: &str, path: &Path, tags: &[String]) -> io::Result<Fragment> {
+    let (delay, command) = line
+        .split_once(':')
+        .ok_or(io::Error::other(FragmentError::InvalidLine))?;
+
+    let mut modifiers = command.split(',');
+
+    let command = Command::from_str(modifiers.next().unwrap()).map_err(io::Error::other)?;
+
+    let delay: f32 = delay.parse().map_err(io::Error::other)?;
+
+    let mut speed: f32 = 1.0;
+    let mut motion = MotionType::Constant;
+    let mut skin = None;
+    let mut sample = None;
+
+    for modifier in modifiers {
+        match modifier {
+            "accel" => motion = MotionType::Accelerate,
+            "decel" => motion = MotionType::Decelerate,
+            "wave" => motion = MotionType::Wave,
+            _ if modifier.starts_with('@') => {
+                let name = &modifier[1..];
+                let hit_tag = format!("{}_hit", name);
+
+                if !tags.iter().any(|tag| tag == name) || !tags.contains(&hit_tag) {
+                    return Err(io::Error::other(FragmentError::UnknownSkin(
+                        name.to_owned(),
+                    )));
+                }
+
+                if command.notes() == 0 {
+                    return Err(io::Error::other(FragmentError::SkinWithoutNote));
+                }
+
+                // Ghost notes always use their own fading sprite
+                if matches!(command, Command::Ghost(_) | Command::GhostBoth) {
+                    return Err(io::Error::other(FragmentError::SkinOnGhost));
+                }
+
+                skin = Some(name.to_owned());
+            }
+            _ if modifier.starts_with('#') => {
+                let name = &modifier[1..];
+                let sample_path = path.with_file_name(name);
+
+                if !sample_path.is_file() {
+                    return Err(io::Error::other(FragmentError::MissingSample));
+                }
+
+                if read_sample_rate(&sample_path)? != SAMPLE_RATE {
+                    return Err(io::Error::other(FragmentError::InvalidSample));
+                }
+
+                if command.notes() == 0 {
+                    return Err(io::Error::other(FragmentError::SampleWithoutNote));
+                }
+
+                sample = Some(name.to_owned());
+            }
+            _ => {
+                let multiplier = modifier
+                    .strip_prefix('x')
+                    .ok_or(io::Error::other(FragmentError::UnknownModifier))?;
+
+                speed = multiplier.parse().map_err(io::Error::other)?;
+
+                if !(speed.is_finite() && speed > 0.0) {
+                    return Err(io::Error::other(FragmentError::InvalidSpeed));
+                }
+            }
+        }
+    }
+
+    Ok(Fragment {
+        command,
+        delay,
+        speed,
+        motion,
+        skin,
+        sample,
+    })
+}
+
+fn read_fragments(path: &Path, tags: &[String]) -> io::Result<Vec<Fragment>> {
     let file = fs::read(path).unwrap();
     let mut fragments = Vec::new();
 
-    for line in file.lines() {
+    for (number, line) in file.lines().enumerate() {
+        let fragment = read_fragment(&line?, path, tags)
+            .map_err(|error| io::Error::other(format!("Line {}: {}", number + 1, error)))?;
+
+        fragments.push(fragment);
+    }
+
+    Ok(fragments)
+}
+
+/// Reads the optional metadata file, returning the song's theme
+fn read_metadata(path: &Path) -> io::Result<Theme> {
+    let mut theme = Theme::Standard;
+
+    if !path.exists() {
+        return Ok(theme);
+    }
+
+    for line in fs::read(path)?.lines() {
         let line = line?;
 
-        let (delay, command) = line
-            .split_once(':')
-            .ok_or(io::Error::other(FragmentError::InvalidLine))?;
+        if line.trim().is_empty() {
+            continue;
+        }
 
-        let command = Command::from_str(command).map_err(io::Error::other)?;
+        let (key, value) = line
+            .split_once('=')
+            .ok_or(io::Error::other(MetadataError::InvalidLine))?;
 
-        let delay: f32 = delay.parse().map_err(io::Error::other)?;
+        match key.trim() {
+            "theme" => theme = Theme::from_str(value.trim()).map_err(io::Error::other)?,
+            _ => return Err(io::Error::other(MetadataError::UnknownKey)),
+        }
+    }
 
-        let fragment = Fragment { command, delay };
+    Ok(theme)
+}
 
-        fragments.push(fragment);
+/// Reads the width and height of a png or aseprite image
+fn read_image_size(path: &Path) -> io::Result<(u32, u32)> {
+    let data = fs::read(path)?;
+
+    let size = if data.starts_with(b"\x89PNG") && data.len() >= 24 {
+        // Big endian, in the IHDR chunk
+        (
+            u32::from_be_bytes(data[16..20].try_into().unwrap()),
+            u32::from_be_bytes(data[20..24].try_into().unwrap()),
+        )
+    } else if data.len() >= 12 && data[4..6] == [0xE0, 0xA5] {
+        (
+            u16::from_le_bytes([data[8], data[9]]) as u32,
+            u16::from_le_bytes([data[10], data[11]]) as u32,
+        )
+    } else {
+        return Err(io::Error::other(BackgroundError::UnknownFormat));
+    };
+
+    Ok(size)
+}
+
+/// Finds the song's own background, if it has one
+fn read_background(path: &Path) -> io::Result<Option<PathBuf>> {
+    let background = match BACKGROUND_FILES
+        .iter()
+        .map(|name| path.join(name))
+        .find(|background| background.exists())
+    {
+        Some(background) => background,
+        None => return Ok(None),
+    };
+
+    let (width, height) = read_image_size(&background)?;
+
+    if (width, height) != (BACKGROUND_WIDTH, BACKGROUND_HEIGHT) {
+        return Err(io::Error::other(BackgroundError::InvalidSize(
+            width, height,
+        )));
     }
 
-    Ok(fragments)
+    Ok(Some(background))
 }
 
-fn read_song(path: &Path) -> io::Result<Map> {
+fn read_song(path: &Path, tags: &[String]) -> io::Result<Map> {
     Ok(Map {
+        song_dir: path.to_owned(),
         song_file: path.join("song.wav"),
-        fragments: read_fragments(&path.join("fragments.txt"))?,
+        fragments: read_fragments(&path.join("fragments.txt"), tags)?,
+        theme: read_metadata(&path.join("metadata.txt"))?,
+        background: read_background(path)?,
     })
 }
 
-fn write_song<F>(mut file: F, song_name: &str, song: Map) -> io::Result<()>
+fn write_song<F>(mut file: F, song_name: &str, song: Map, root: &Path) -> io::Result<()>
 where
     F: Write,
 {
@@ -103,7 +563,7 @@ where
     writeln!(file, "use agb::include_wav;",)?;
     writeln!(
         file,
-        "use crate::song_data::{{Track, Command, Fragment, SongData}};",
+        "use crate::song_data::{{Track, Command, Fragment, Motion, MotionType, Skin, SongData, Theme}};",
     )?;
 
     writeln!(
@@ -112,26 +572,100 @@ where
         song.song_file.to_str().unwrap(),
     )?;
 
+    // Each keysound is embedded once, however many notes use it
+    let mut samples: Vec<String> = Vec::new();
+
+    for name in song
+        .fragments
+        .iter()
+        .filter_map(|fragment| fragment.sample.as_ref())
+    {
+        if !samples.contains(name) {
+            samples.push(name.clone());
+        }
+    }
+
+    for (i, name) in samples.iter().enumerate() {
+        writeln!(
+            file,
+            "const SAMPLE_{}: &[u8] = include_wav!(\"{}\");",
+            i,
+            song.song_dir.join(name).to_str().unwrap(),
+        )?;
+    }
+
+    let background = match &song.background {
+        Some(path) => {
+            writeln!(
+                file,
+                "agb::include_background_gfx!(background, tiles => \"{}\", layers => deduplicate \"{}\");",
+                root.join(BACKGROUND_TILES).to_str().unwrap(),
+                path.to_str().unwrap(),
+            )?;
+            writeln!(
+                file,
+                "const BACKGROUND: crate::song_data::Background = crate::song_data::Background::new(&background::layers, &background::tiles, background::PALETTES);",
+            )?;
+
+            "Some(&BACKGROUND)"
+        }
+        None => "None",
+    };
+
+    let notes: usize = song
+        .fragments
+        .iter()
+        .map(|fragment| fragment.command.notes())
+        .sum();
+
     writeln!(
         file,
-        "pub const SONG: SongData<{}> = SongData::new(\"{}\", [",
+        "pub const SONG: SongData<{}> = SongData::new(\"{}\", {}, [",
         song.fragments.len(),
         song_name,
+        notes,
     )?;
 
-    for fragment in song.fragments {
-        let frame = ((fragment.delay * 60.0) / 1000.0) - 146.0;
-        let frame: usize = frame.round() as usize;
+    // Slower notes start earlier, so keep the fragments in the order they run
+    let mut fragments = song.fragments;
+    fragments.sort_by_key(|fragment| fragment.frame());
+
+    // Notes that spawn before the music starts delay it, so they still arrive on time
+    let lead_in = fragments
+        .first()
+        .map_or(0, |fragment| fragment.frame().min(0).unsigned_abs());
+
+    for fragment in fragments {
+        let skin = fragment.skin.as_deref().unwrap_or(DEFAULT_SKIN);
+
+        let sample = match &fragment.sample {
+            Some(name) => format!(
+                "Some(SAMPLE_{})",
+                samples.iter().position(|sample| sample == name).unwrap()
+            ),
+            None => "None".to_string(),
+        };
 
         writeln!(
             file,
-            "Fragment::new({}, {}),",
+            "Fragment::new({}, {}, Motion::new({}, {}), Skin::new(\"{}\", \"{}_hit\"), {}),",
             fragment.command.to_ingame_command(),
-            frame
+            fragment.frame() + lead_in as i32,
+            fragment.motion.to_ingame_motion_type(),
+            fragment.travel() as usize,
+            skin,
+            skin,
+            sample
         )?;
     }
 
-    writeln!(file, "], SOUND );",)?;
+    writeln!(
+        file,
+        "], SOUND, {}, {}, {} );",
+        lead_in,
+        song.theme.to_ingame_theme(),
+        background
+    )?;
 
     writeln!(file, "}}",)
 }
@@ -167,6 +701,8 @@ fn main() {
 
     let mut names = Vec::new();
 
+    let tags = read_tags(&current_dir.join(NOTE_SPRITES)).unwrap();
+
     for file in fs::read_dir(songs_dir).unwrap() {
         let path = file.unwrap().path();
         let song_name = path.file_stem().unwrap().to_str().unwrap();
@@ -175,13 +711,17 @@ fn main() {
 
         names.push(song_name.to_owned());
 
-        match read_song(&path) {
-            Ok(song) => write_song(&gen_file, song_name, song).unwrap(),
-            Err(error) => error!("Failed to parse song {:?}: {}", path, error),
+        match read_song(&path, &tags) {
+            Ok(song) => write_song(&gen_file, song_name, song, &current_dir).unwrap(),
+            Err(error) => {
+                error!("Failed to parse song {:?}: {}", path, error);
+                panic!("Failed to parse song {:?}: {}", path, error);
+            }
         }
     }
 
     write_songs_info(&gen_file, &names).unwrap();
 
     println!("cargo:rerun-if-changed=songs/");
+    println!("cargo:rerun-if-changed={}", NOTE_SPRITES);
 }
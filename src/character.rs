@@ -0,0 +1,107 @@
+use crate::skill::{Armour, Footwork, Glory, NoSkill, Scavenger, Skill};
+
+// Saved by index, so new characters go on the end
+pub const CHARACTERS_COUNT: usize = 5;
+pub const CHARACTERS: [Character; CHARACTERS_COUNT] = [
+    Character::Runner,
+    Character::Knight,
+    Character::Rogue,
+    Character::Paladin,
+    Character::Ranger,
+];
+
+pub enum Animation {
+    Running,
+    AttackLow,
+    AttackHigh,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Hash)]
+pub enum Character {
+    #[default]
+    Runner,
+    Knight,
+    Rogue,
+    Paladin,
+    Ranger,
+}
+
+impl Character {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Character::Runner => "Runner",
+            Character::Knight => "Knight",
+            Character::Rogue => "Rogue",
+            Character::Paladin => "Paladin",
+            Character::Ranger => "Ranger",
+        }
+    }
+
+    pub fn skill(&self) -> &'static dyn Skill {
+        match self {
+            Character::Runner => &NoSkill,
+            Character::Knight => &Armour,
+            Character::Rogue => &Scavenger,
+            Character::Paladin => &Glory,
+            Character::Ranger => &Footwork,
+        }
+    }
+
+    /// Sprite tag for the animation, running uses a different tag during fever
+    pub fn tag(&self, animation: &Animation, fever: bool) -> &'static str {
+        match self {
+            Character::Runner => match animation {
+                Animation::Running if fever => "player_fever_run",
+                Animation::Running => "player_run",
+                Animation::AttackLow => "player_attack_low",
+                Animation::AttackHigh => "player_attack_high",
+            },
+            Character::Knight => match animation {
+                Animation::Running if fever => "knight_fever_run",
+                Animation::Running => "knight_run",
+                Animation::AttackLow => "knight_attack_low",
+                Animation::AttackHigh => "knight_attack_high",
+            },
+            // Recoloured characters share the fever animation they're based on
+            Character::Rogue => match animation {
+                Animation::Running if fever => "player_fever_run",
+                Animation::Running => "rogue_run",
+                Animation::AttackLow => "rogue_attack_low",
+                Animation::AttackHigh => "rogue_attack_high",
+            },
+            Character::Paladin => match animation {
+                Animation::Running if fever => "knight_fever_run",
+                Animation::Running => "paladin_run",
+                Animation::AttackLow => "paladin_attack_low",
+                Animation::AttackHigh => "paladin_attack_high",
+            },
+            Character::Ranger => match animation {
+                Animation::Running if fever => "player_fever_run",
+                Animation::Running => "ranger_run",
+                Animation::AttackLow => "ranger_attack_low",
+                Animation::AttackHigh => "ranger_attack_high",
+            },
+        }
+    }
+
+    /// Index of the character, as stored in save data
+    pub fn to_u8(self) -> u8 {
+        self as u8
+    }
+
+    pub fn next(&self) -> Self {
+        CHARACTERS[(*self as usize + 1) % CHARACTERS_COUNT]
+    }
+
+    pub fn previous(&self) -> Self {
+        CHARACTERS[(*self as usize + CHARACTERS_COUNT - 1) % CHARACTERS_COUNT]
+    }
+}
+
+impl TryFrom<u8> for Character {
+    type Error = ();
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        CHARACTERS.get(value as usize).copied().ok_or(())
+    }
+}
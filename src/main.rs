@@ -13,11 +13,17 @@ use states::{Callback, SetState, State};
 
 extern crate alloc;
 
+mod character;
+mod rtc;
 mod save_data;
 mod score;
+mod settings;
+mod sfx;
+mod skill;
 mod song_data;
 mod songs;
 mod states;
+mod stats;
 
 const FONT: Font = include_font!("assets/80s-retro-future.ttf", 13);
 const BIG_FONT: Font = include_font!("assets/PixeloidSans-Bold.ttf", 9);
@@ -25,13 +31,18 @@ const BIG_FONT: Font = include_font!("assets/PixeloidSans-Bold.ttf", 9);
 #[agb::entry]
 fn main(mut gba: agb::Gba) -> ! {
     let object_gfx = gba.display.object.get_managed();
-    let (video_gfx, mut vram) = gba.display.video.tiled1();
+    let (video_gfx, mut vram) = gba.display.video.tiled0();
     let mut input = ButtonController::new();
     let mut mixer = gba.mixer.mixer(Frequency::Hz32768);
+    mixer.enable();
     let vblank = agb::interrupt::VBlank::get();
-    let mut save_data = SaveDataManager::load(&mut gba.save).unwrap();
+    let mut save_data = SaveDataManager::load(gba.save);
 
-    let mut state: Box<dyn State> = Box::new(states::MainMenuState::new(&object_gfx));
+    let mut state: Box<dyn State> = if save_data.take_warning() {
+        Box::new(states::SaveWarningState::new(SetState::MainMenu))
+    } else {
+        Box::new(states::MainMenuState::new(&object_gfx))
+    };
 
     state.init(
         &mut save_data,
@@ -48,10 +59,20 @@ fn main(mut gba: agb::Gba) -> ! {
         // Update current state
         match state.update(&mut save_data, &object_gfx, &mut vram, &mut mixer, &input) {
             Callback::None => (),
-            Callback::SetState(new_state) => {
+            Callback::SetState(mut new_state) => {
+                // Let the player know saving failed before moving on
+                if save_data.take_warning() {
+                    new_state = SetState::SaveWarning(Box::new(new_state));
+                }
+
                 match new_state {
                     SetState::MainMenu => state = Box::new(states::MainMenuState::new(&object_gfx)),
                     SetState::SongMenu => state = Box::new(states::SongMenuState::new(&object_gfx)),
+                    SetState::Profile => state = Box::new(states::ProfileState::new()),
+                    SetState::CharacterSelect => {
+                        state = Box::new(states::CharacterSelectState::new(&object_gfx))
+                    }
+                    SetState::Settings => state = Box::new(states::SettingsState::new(&object_gfx)),
                     SetState::Song(song_id) => {
                         state = Box::new(states::SongState::new(song_id, &object_gfx))
                     }
@@ -61,6 +82,9 @@ fn main(mut gba: agb::Gba) -> ! {
                     SetState::SongInfo(song_id) => {
                         state = Box::new(states::SongInfoState::new(song_id))
                     }
+                    SetState::SaveWarning(next) => {
+                        state = Box::new(states::SaveWarningState::new(*next))
+                    }
                 }
                 state.init(
                     &mut save_data,
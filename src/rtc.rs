@@ -0,0 +1,154 @@
+use core::fmt::Display;
+
+#[derive(Debug, PartialEq, Clone, Copy, Hash)]
+pub struct Date {
+    year: u8, // Years since 2000
+    month: u8,
+    day: u8,
+}
+
+impl Date {
+    pub const PACKED_SIZE: usize = 2;
+
+    /// Packs the date into 7 bits of year, 4 of month and 5 of day
+    pub fn pack(&self) -> [u8; Self::PACKED_SIZE] {
+        let packed = (self.year as u16) << 9 | (self.month as u16) << 5 | self.day as u16;
+        packed.to_le_bytes()
+    }
+
+    pub fn unpack(bytes: [u8; Self::PACKED_SIZE]) -> Option<Self> {
+        let packed = u16::from_le_bytes(bytes);
+        let date = Self {
+            year: (packed >> 9) as u8,
+            month: (packed >> 5 & 0xF) as u8,
+            day: (packed & 0x1F) as u8,
+        };
+
+        if (1..=12).contains(&date.month) && (1..=31).contains(&date.day) {
+            Some(date)
+        } else {
+            None
+        }
+    }
+}
+
+/// Shown as dd/mm, to fit at the end of a score row
+impl Display for Date {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{:02}/{:02}", self.day, self.month)
+    }
+}
+
+/// Reads the current date from the cart's real-time clock, if it has one
+#[cfg(feature = "rtc")]
+pub fn date() -> Option<Date> {
+    agb::interrupt::free(|_| s3511::read_date())
+}
+
+#[cfg(not(feature = "rtc"))]
+pub fn date() -> Option<Date> {
+    None
+}
+
+/// Seiko S-3511 RTC, connected through the cart's GPIO port
+#[cfg(feature = "rtc")]
+mod s3511 {
+    use super::Date;
+
+    const GPIO_DATA: *mut u16 = 0x0800_00C4 as *mut u16;
+    const GPIO_DIRECTION: *mut u16 = 0x0800_00C6 as *mut u16;
+    const GPIO_CONTROL: *mut u16 = 0x0800_00C8 as *mut u16;
+
+    const SCK: u16 = 1 << 0;
+    const SIO: u16 = 1 << 1;
+    const CS: u16 = 1 << 2;
+
+    const COMMAND_READ_DATE_TIME: u8 = 0x65;
+
+    fn set(register: *mut u16, value: u16) {
+        unsafe { register.write_volatile(value) }
+    }
+
+    fn get(register: *mut u16) -> u16 {
+        unsafe { register.read_volatile() }
+    }
+
+    fn write_byte(value: u8) {
+        // Sent most significant bit first, latched on the rising clock edge
+        for i in (0..8).rev() {
+            let bit = ((value >> i) as u16 & 1) << 1;
+
+            set(GPIO_DATA, bit | CS);
+            set(GPIO_DATA, bit | CS);
+            set(GPIO_DATA, bit | CS);
+            set(GPIO_DATA, bit | CS | SCK);
+        }
+    }
+
+    fn read_byte() -> u8 {
+        // Received least significant bit first
+        let mut value = 0;
+
+        for _ in 0..8 {
+            set(GPIO_DATA, CS);
+            set(GPIO_DATA, CS);
+            set(GPIO_DATA, CS);
+            set(GPIO_DATA, CS);
+            set(GPIO_DATA, CS);
+            set(GPIO_DATA, CS | SCK);
+
+            let bit = ((get(GPIO_DATA) & SIO) >> 1) as u8;
+            value = (value >> 1) | (bit << 7);
+        }
+
+        value
+    }
+
+    fn from_bcd(value: u8) -> Option<u8> {
+        let (tens, ones) = (value >> 4, value & 0xF);
+
+        if tens < 10 && ones < 10 {
+            Some(tens * 10 + ones)
+        } else {
+            None
+        }
+    }
+
+    pub fn read_date() -> Option<Date> {
+        set(GPIO_CONTROL, 1);
+
+        set(GPIO_DATA, SCK);
+        set(GPIO_DATA, SCK | CS);
+        set(GPIO_DIRECTION, SCK | SIO | CS);
+
+        write_byte(COMMAND_READ_DATE_TIME);
+
+        // Switch SIO to input for the reply
+        set(GPIO_DIRECTION, SCK | CS);
+
+        let year = read_byte();
+        let month = read_byte() & 0x1F;
+        let day = read_byte() & 0x3F;
+
+        // Remaining weekday and time bytes are unused
+        for _ in 0..4 {
+            read_byte();
+        }
+
+        set(GPIO_DATA, SCK);
+        set(GPIO_DATA, SCK);
+
+        let date = Date {
+            year: from_bcd(year)?,
+            month: from_bcd(month)?,
+            day: from_bcd(day)?,
+        };
+
+        // Without an RTC the port reads back garbage
+        if (1..=12).contains(&date.month) && (1..=31).contains(&date.day) {
+            Some(date)
+        } else {
+            None
+        }
+    }
+}
@@ -1,24 +1,254 @@
-use core::hash::{Hash, Hasher};
+use core::hash::Hasher;
 
 use crate::{
-    score::Score,
+    character::Character,
+    rtc,
+    score::{HighScore, Score},
+    settings::Settings,
     songs::{SongID, SONGS_COUNT},
+    stats::{Profile, SongStats},
 };
 use agb::save;
+use alloc::{vec, vec::Vec};
+use media::SCORES_PER_SONG;
 
-const SCORES_PER_SONG: usize = 5;
+// The save record holds every song in the build, as many as fit on the media:
+// hash | version | song count | play counter | profile | settings | songs
+// The song count is stored so a save from a build with fewer songs still loads
+const VERSION: u8 = 2;
 const HASH_SIZE: usize = core::mem::size_of::<u64>();
-const SAVE_DATA_SIZE: usize = core::mem::size_of::<SaveData>();
+const HEADER_SIZE: usize = HASH_SIZE + 1 + 2 + 2 + Profile::PACKED_SIZE + Settings::PACKED_SIZE;
+const SONG_SIZE: usize = SongStats::PACKED_SIZE + SCORES_PER_SONG * HighScore::PACKED_SIZE;
+const SONG_SLOTS: usize = (media::SIZE - HEADER_SIZE) / SONG_SIZE;
+
+#[cfg(not(any(
+    feature = "save-sram",
+    feature = "save-flash-64k",
+    feature = "save-flash-128k",
+    feature = "save-eeprom-512b",
+    feature = "save-eeprom-8k"
+)))]
+compile_error!("No save media selected, enable one of the `save-*` features");
+
+// `save-sram` is a default feature, so it has to be disabled to pick another media
+macro_rules! exclusive_media {
+    ($($first:tt, $second:tt;)*) => {
+        $(
+            #[cfg(all(feature = $first, feature = $second))]
+            compile_error!(concat!(
+                "Save media `", $first, "` and `", $second, "` are both selected, enable only one ",
+                "`save-*` feature and use `--no-default-features` to disable the default `save-sram`"
+            ));
+        )*
+    };
+}
+
+exclusive_media! {
+    "save-sram", "save-flash-64k";
+    "save-sram", "save-flash-128k";
+    "save-sram", "save-eeprom-512b";
+    "save-sram", "save-eeprom-8k";
+    "save-flash-64k", "save-flash-128k";
+    "save-flash-64k", "save-eeprom-512b";
+    "save-flash-64k", "save-eeprom-8k";
+    "save-flash-128k", "save-eeprom-512b";
+    "save-flash-128k", "save-eeprom-8k";
+    "save-eeprom-512b", "save-eeprom-8k";
+}
+
+#[cfg(feature = "save-sram")]
+mod media {
+    pub const SIZE: usize = 32 * 1024;
+    pub const SCORES_PER_SONG: usize = 5;
+
+    pub fn init(save_manager: &mut agb::save::SaveManager) {
+        save_manager.init_sram();
+    }
+}
+
+#[cfg(feature = "save-flash-64k")]
+mod media {
+    pub const SIZE: usize = 64 * 1024;
+    pub const SCORES_PER_SONG: usize = 5;
+
+    pub fn init(save_manager: &mut agb::save::SaveManager) {
+        save_manager.init_flash_64k();
+    }
+}
+
+#[cfg(feature = "save-flash-128k")]
+mod media {
+    pub const SIZE: usize = 128 * 1024;
+    pub const SCORES_PER_SONG: usize = 5;
+
+    pub fn init(save_manager: &mut agb::save::SaveManager) {
+        save_manager.init_flash_128k();
+    }
+}
+
+#[cfg(feature = "save-eeprom-512b")]
+mod media {
+    pub const SIZE: usize = 512;
+    pub const SCORES_PER_SONG: usize = 3; // Fewer scores so more songs fit
+
+    pub fn init(save_manager: &mut agb::save::SaveManager) {
+        save_manager.init_eeprom_512b();
+    }
+}
+
+#[cfg(feature = "save-eeprom-8k")]
+mod media {
+    pub const SIZE: usize = 8 * 1024;
+    pub const SCORES_PER_SONG: usize = 5;
+
+    pub fn init(save_manager: &mut agb::save::SaveManager) {
+        save_manager.init_eeprom_8k();
+    }
+}
+
+// Fails the build if there are more songs than fit on the selected media
+const SAVE_SIZE: usize = {
+    if SONGS_COUNT > SONG_SLOTS {
+        panic!("Too many songs to fit on the selected save media");
+    }
+
+    HEADER_SIZE + SONGS_COUNT * SONG_SIZE
+};
+
+/// Writes fields one after another into the save record
+struct RecordWriter<'a> {
+    record: &'a mut [u8],
+    position: usize,
+}
+
+impl<'a> RecordWriter<'a> {
+    fn new(record: &'a mut [u8]) -> Self {
+        Self {
+            record,
+            position: 0,
+        }
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        self.record[self.position..self.position + bytes.len()].copy_from_slice(bytes);
+        self.position += bytes.len();
+    }
+}
+
+/// Reads fields back out of the save record in the order they were written
+struct RecordReader<'a> {
+    record: &'a [u8],
+    position: usize,
+}
+
+impl<'a> RecordReader<'a> {
+    fn new(record: &'a [u8]) -> Self {
+        Self {
+            record,
+            position: 0,
+        }
+    }
+
+    fn read<const N: usize>(&mut self) -> [u8; N] {
+        let mut bytes = [0; N];
+        bytes.copy_from_slice(&self.record[self.position..self.position + N]);
+        self.position += N;
+        bytes
+    }
+}
 
-#[derive(Hash)]
 pub struct SaveData {
-    scores: [[Option<Score>; SCORES_PER_SONG]; SONGS_COUNT],
+    scores: [[Option<HighScore>; SCORES_PER_SONG]; SONGS_COUNT],
+    stats: [SongStats; SONGS_COUNT],
+    profile: Profile,
+    play_counter: u16,
+    settings: Settings,
 }
 
 impl SaveData {
-    fn insert_score(&mut self, song_id: SongID, score: Score) {
+    fn encode(&self) -> Vec<u8> {
+        let mut record = vec![0; SAVE_SIZE];
+        let (hash, body) = record.split_at_mut(HASH_SIZE);
+
+        let mut writer = RecordWriter::new(body);
+        writer.write(&[VERSION]);
+        writer.write(&(SONGS_COUNT as u16).to_le_bytes());
+        writer.write(&self.play_counter.to_le_bytes());
+        writer.write(&self.profile.pack());
+        writer.write(&self.settings.pack());
+
+        for (stats, scores) in self.stats.iter().zip(&self.scores) {
+            writer.write(&stats.pack());
+
+            for score in scores {
+                // Empty slots are zeroed, which a recorded score never is
+                writer.write(&score.map_or([0; HighScore::PACKED_SIZE], |score| score.pack()));
+            }
+        }
+
+        hash.copy_from_slice(&get_hash(body));
+
+        record
+    }
+
+    /// Returns the length of the record starting with this header, or None if it
+    /// has more songs than fit on the media, as erased media does
+    fn record_size(header: &[u8; HEADER_SIZE]) -> Option<usize> {
+        let mut reader = RecordReader::new(&header[HASH_SIZE + 1..]);
+        let songs = u16::from_le_bytes(reader.read()) as usize;
+
+        (songs <= SONG_SLOTS).then_some(HEADER_SIZE + songs * SONG_SIZE)
+    }
+
+    /// Returns None if the record is corrupt, erased or from another version
+    fn decode(record: &[u8]) -> Option<Self> {
+        let (hash, body) = record.split_at(HASH_SIZE);
+
+        // Checked before anything is read, so only a record this wrote gets decoded
+        if hash != get_hash(body) {
+            return None;
+        }
+
+        let mut reader = RecordReader::new(body);
+
+        if reader.read::<1>() != [VERSION] {
+            return None;
+        }
+
+        let songs = u16::from_le_bytes(reader.read()) as usize;
+
+        if record.len() != HEADER_SIZE + songs * SONG_SIZE {
+            return None;
+        }
+
+        let mut data = Self {
+            play_counter: u16::from_le_bytes(reader.read()),
+            profile: Profile::unpack(reader.read())?,
+            settings: Settings::unpack(reader.read())?,
+            ..Self::default()
+        };
+
+        // Songs past the ones the build has are dropped, missing ones start fresh
+        for (stats, scores) in data.stats.iter_mut().zip(&mut data.scores).take(songs) {
+            *stats = SongStats::unpack(reader.read())?;
+
+            for score in scores {
+                let bytes = reader.read();
+
+                if bytes != [0; HighScore::PACKED_SIZE] {
+                    *score = Some(HighScore::unpack(bytes)?);
+                }
+            }
+        }
+
+        Some(data)
+    }
+
+    /// Returns the rank the score was inserted at, or None if it didn't make the table
+    fn insert_score(&mut self, song_id: SongID, score: HighScore) -> Option<usize> {
         let song_index: usize = song_id.into();
         let mut new_score = score;
+        let mut rank = None;
 
         for i in 0..SCORES_PER_SONG {
             let current = self.scores[song_index].get_mut(i).unwrap();
@@ -27,92 +257,226 @@ impl SaveData {
                 if current_score.score() < new_score.score() {
                     // Copy score down
                     core::mem::swap(current_score, &mut new_score);
+                    rank.get_or_insert(i);
                 }
             } else {
                 *current = Some(new_score);
-                return;
+                return rank.or(Some(i));
             }
         }
+
+        rank
     }
 }
 
-const DEFAULT_SCORE: Option<Score> = None;
+const DEFAULT_SCORE: Option<HighScore> = None;
 
 impl Default for SaveData {
     fn default() -> Self {
         Self {
             scores: [[DEFAULT_SCORE; SCORES_PER_SONG]; SONGS_COUNT],
+            stats: [SongStats::default(); SONGS_COUNT],
+            profile: Profile::default(),
+            play_counter: 0,
+            settings: Settings::default(),
         }
     }
 }
 
 pub struct SaveDataManager {
     data: SaveData,
-    access: save::SaveData,
+    save_manager: save::SaveManager, // Kept to access the media again after a failure
+    access: Option<save::SaveData>,  // None when the save media couldn't be accessed
+    loaded: bool,                    // Saved data was read, so writing can't overwrite it
+    failed: bool,                    // Data is only kept in memory until a write succeeds
+    warn: bool,                      // Failure hasn't been shown to the player yet
 }
 
 impl SaveDataManager {
-    pub fn load(save_manager: &mut save::SaveManager) -> Result<Self, save::Error> {
-        save_manager.init_sram();
+    /// If the save media can't be read, data is kept in memory only until a save succeeds
+    pub fn load(mut save_manager: save::SaveManager) -> Self {
+        media::init(&mut save_manager);
+
+        match Self::read(&mut save_manager) {
+            Ok((data, access)) => Self {
+                // Erased or corrupt save media starts with fresh data
+                data: data.unwrap_or_default(),
+                save_manager,
+                access: Some(access),
+                loaded: true,
+                failed: false,
+                warn: false,
+            },
+            Err(_) => Self {
+                data: SaveData::default(),
+                save_manager,
+                access: None,
+                loaded: false,
+                failed: true,
+                warn: true,
+            },
+        }
+    }
 
+    fn read(
+        save_manager: &mut save::SaveManager,
+    ) -> Result<(Option<SaveData>, save::SaveData), save::Error> {
         let mut access = save_manager.access()?;
-        let mut hash_buf = [0; HASH_SIZE];
-        let mut data_buf = [0; SAVE_DATA_SIZE];
+        let mut header = [0; HEADER_SIZE];
+
+        access.read(0, &mut header)?;
 
-        access.read(0, &mut hash_buf).unwrap();
-        access.read(HASH_SIZE, &mut data_buf).unwrap();
+        let Some(size) = SaveData::record_size(&header) else {
+            return Ok((None, access));
+        };
 
-        let data: SaveData = unsafe { core::mem::transmute(data_buf) };
+        let mut record = vec![0; size];
 
-        let loaded_hash = get_hash(&data);
+        access.read(0, &mut record)?;
 
-        if loaded_hash != hash_buf {
-            // Error loading
-            Ok(Self {
-                data: SaveData::default(),
-                access,
-            })
-        } else {
-            Ok(Self { data, access })
+        Ok((SaveData::decode(&record), access))
+    }
+
+    /// Reads the save media again if it couldn't be read when loading, so the saved
+    /// data replaces the in-memory data before anything is written over it
+    fn recover(&mut self) {
+        if self.loaded {
+            return;
         }
+
+        // The old handle holds the media lock, it has to go first
+        self.access = None;
+
+        if let Ok((data, access)) = Self::read(&mut self.save_manager) {
+            // Erased or corrupt save media has nothing to lose, keep the in-memory data
+            if let Some(data) = data {
+                self.data = data;
+            }
+
+            self.access = Some(access);
+            self.loaded = true;
+        }
+    }
+
+    /// Whether changes are currently being written to the save media
+    pub fn saving(&self) -> bool {
+        !self.failed
+    }
+
+    /// Returns true once after saving starts failing, so the player can be warned
+    pub fn take_warning(&mut self) -> bool {
+        core::mem::take(&mut self.warn)
     }
 
     fn save(&mut self) {
-        let hash = get_hash(&self.data);
+        match self.write() {
+            Ok(()) => self.failed = false,
+            Err(_) => {
+                // Only warn when saving starts failing, not on every retry
+                if !self.failed {
+                    self.warn = true;
+                }
+
+                self.failed = true;
+            }
+        }
+    }
 
-        let mut writer = self
-            .access
-            .prepare_write(0..(HASH_SIZE + SAVE_DATA_SIZE))
-            .unwrap();
+    fn write(&mut self) -> Result<(), save::Error> {
+        // Never write over saved data that hasn't been read
+        if !self.loaded {
+            return Err(save::Error::NoMedia);
+        }
 
-        writer.write_and_verify(0, &hash).unwrap();
+        // Access the media again after a failure, in case it was reinserted
+        if self.failed || self.access.is_none() {
+            // The old handle holds the media lock, it has to go first
+            self.access = None;
+            self.access = Some(self.save_manager.access()?);
+        }
 
-        let data = unsafe {
-            core::slice::from_raw_parts(&self.data as *const _ as *const u8, SAVE_DATA_SIZE)
-        };
+        let access = self.access.as_mut().ok_or(save::Error::NoMedia)?;
 
-        writer.write_and_verify(HASH_SIZE, data).unwrap();
+        let record = self.data.encode();
+
+        let mut writer = access.prepare_write(0..SAVE_SIZE)?;
+
+        writer.write_and_verify(0, &record)
     }
 
-    pub fn insert_score(&mut self, song_id: SongID, score: Score) {
-        self.data.insert_score(song_id, score);
+    pub fn insert_score(&mut self, song_id: SongID, score: Score) -> Option<usize> {
+        self.recover();
+
+        let song_index: usize = song_id.into();
+
+        self.data.play_counter = self.data.play_counter.saturating_add(1);
+
+        self.data.stats[song_index].add_result(&score);
+
+        // Failed runs count towards stats but not the score table
+        let rank = if score.cleared() {
+            let high_score = HighScore::new(&score, self.data.play_counter, rtc::date());
+            self.data.insert_score(song_id, high_score)
+        } else {
+            None
+        };
+
         self.save();
+
+        rank
     }
 
-    pub fn get_scores(&mut self, song_id: SongID) -> [Option<Score>; SCORES_PER_SONG] {
+    pub fn get_scores(&mut self, song_id: SongID) -> [Option<HighScore>; SCORES_PER_SONG] {
         let song_index: usize = song_id.into();
         self.data.scores[song_index]
     }
 
+    pub fn get_stats(&mut self, song_id: SongID) -> SongStats {
+        let song_index: usize = song_id.into();
+        self.data.stats[song_index]
+    }
+
+    pub fn get_profile(&self) -> Profile {
+        self.data.profile
+    }
+
+    pub fn set_name(&mut self, name: &[u8]) {
+        self.recover();
+        self.data.profile.set_name(name);
+        self.save();
+    }
+
+    pub fn set_character(&mut self, character: Character) {
+        self.recover();
+        self.data.profile.set_character(character);
+        self.save();
+    }
+
+    /// Only kept in memory until the next save, to avoid writing every song
+    pub fn add_play_time(&mut self, frames: usize) {
+        self.data.profile.add_play_time(frames);
+    }
+
+    pub fn get_settings(&self) -> Settings {
+        self.data.settings
+    }
+
+    pub fn set_settings(&mut self, settings: Settings) {
+        self.recover();
+        self.data.settings = settings;
+        self.save();
+    }
+
     pub fn reset(&mut self) {
+        self.recover();
         self.data = SaveData::default();
         self.save();
     }
 }
 
-fn get_hash(data: &SaveData) -> [u8; HASH_SIZE] {
+fn get_hash(bytes: &[u8]) -> [u8; HASH_SIZE] {
     let mut hasher = rustc_hash::FxHasher::default();
-    data.hash(&mut hasher);
+    hasher.write(bytes);
     let hash = hasher.finish();
     hash.to_be_bytes()
 }
@@ -1,3 +1,8 @@
+use crate::{character::Character, rtc::Date, song_data::Track};
+
+pub const GRADES_COUNT: usize = 7;
+
+#[derive(Clone, Copy)]
 pub enum Grade {
     SSS,
     SS,
@@ -8,8 +13,30 @@ pub enum Grade {
     D,
 }
 
+pub const GRADES: [Grade; GRADES_COUNT] = [
+    Grade::SSS,
+    Grade::SS,
+    Grade::S,
+    Grade::A,
+    Grade::B,
+    Grade::C,
+    Grade::D,
+];
+
 impl Grade {
-    pub fn to_str(&self) -> &str {
+    pub fn from_accuracy(accuracy: u8) -> Self {
+        match accuracy {
+            100..=u8::MAX => Grade::SSS,
+            95..=99 => Grade::SS,
+            90..=94 => Grade::S,
+            80..=89 => Grade::A,
+            70..=79 => Grade::B,
+            60..=69 => Grade::C,
+            0..=59 => Grade::D,
+        }
+    }
+
+    pub fn to_str(self) -> &'static str {
         match self {
             Self::SSS => "SSS",
             Self::SS => "SS",
@@ -21,7 +48,7 @@ impl Grade {
         }
     }
 
-    pub fn to_print_str(&self) -> &str {
+    pub fn to_print_str(self) -> &'static str {
         match self {
             Self::SSS => "S",
             Self::SS => "S",
@@ -34,19 +61,130 @@ impl Grade {
     }
 }
 
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Judgement {
+    Perfect,
+    Great,
+}
+
+#[derive(Debug, Default, PartialEq, Clone, Copy, Hash)]
+pub struct TrackJudgements {
+    perfect: u16,
+    great: u16,
+    miss: u16,
+}
+
+impl TrackJudgements {
+    pub fn perfect(&self) -> u16 {
+        self.perfect
+    }
+
+    pub fn great(&self) -> u16 {
+        self.great
+    }
+
+    pub fn miss(&self) -> u16 {
+        self.miss
+    }
+
+    pub fn hit(&self) -> u16 {
+        self.perfect + self.great
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Clone, Copy, Hash)]
+pub struct Judgements {
+    high: TrackJudgements,
+    low: TrackJudgements,
+    early: u16, // Only counted for greats
+    late: u16,
+}
+
+impl Judgements {
+    fn track_mut(&mut self, track: Track) -> &mut TrackJudgements {
+        match track {
+            Track::High => &mut self.high,
+            Track::Low => &mut self.low,
+        }
+    }
+
+    /// Offset is how far the note was from perfect, positive when hit early
+    pub fn add_hit(&mut self, track: Track, judgement: Judgement, offset: i32) {
+        let judgements = self.track_mut(track);
+
+        match judgement {
+            Judgement::Perfect => judgements.perfect += 1,
+            Judgement::Great => {
+                judgements.great += 1;
+
+                if offset > 0 {
+                    self.early += 1;
+                } else {
+                    self.late += 1;
+                }
+            }
+        }
+    }
+
+    pub fn add_miss(&mut self, track: Track) {
+        self.track_mut(track).miss += 1;
+    }
+
+    pub fn high(&self) -> &TrackJudgements {
+        &self.high
+    }
+
+    pub fn low(&self) -> &TrackJudgements {
+        &self.low
+    }
+
+    pub fn early(&self) -> u16 {
+        self.early
+    }
+
+    pub fn late(&self) -> u16 {
+        self.late
+    }
+
+    pub fn hit(&self) -> usize {
+        (self.high.hit() + self.low.hit()) as usize
+    }
+
+    pub fn great(&self) -> usize {
+        (self.high.great + self.low.great) as usize
+    }
+
+    pub fn miss(&self) -> usize {
+        (self.high.miss + self.low.miss) as usize
+    }
+}
+
 #[derive(Debug, PartialEq, Clone, Copy, Hash)]
 pub struct Score {
     score: usize,
     max_combo: usize,
+    judgements: Judgements,
     accuracy: u8,
+    cleared: bool,        // False if the player ran out of HP
+    character: Character, // Played as, since skills affect the score
 }
 
 impl Score {
-    pub fn new(score: usize, max_combo: usize, accuracy: u8) -> Self {
+    pub fn new(
+        score: usize,
+        max_combo: usize,
+        judgements: Judgements,
+        accuracy: u8,
+        cleared: bool,
+        character: Character,
+    ) -> Self {
         Self {
             score,
             max_combo,
+            judgements,
             accuracy,
+            cleared,
+            character,
         }
     }
 
@@ -55,23 +193,117 @@ impl Score {
     }
 
     pub fn grade(&self) -> Grade {
-        match self.accuracy {
-            100 => Grade::SSS,
-            95..=99 => Grade::SS,
-            90..=94 => Grade::S,
-            80..=89 => Grade::A,
-            70..=79 => Grade::B,
-            60..=69 => Grade::C,
-            0..=59 => Grade::D,
-            _ => unreachable!(),
-        }
+        Grade::from_accuracy(self.accuracy)
     }
 
     pub fn max_combo(&self) -> usize {
         self.max_combo
     }
 
+    pub fn judgements(&self) -> &Judgements {
+        &self.judgements
+    }
+
+    pub fn hit(&self) -> usize {
+        self.judgements.hit()
+    }
+
     pub fn accuracy(&self) -> u8 {
         self.accuracy
     }
+
+    pub fn cleared(&self) -> bool {
+        self.cleared
+    }
+
+    pub fn character(&self) -> Character {
+        self.character
+    }
+
+    pub fn full_combo(&self) -> bool {
+        self.judgements.miss() == 0
+    }
+
+    pub fn all_perfect(&self) -> bool {
+        self.full_combo() && self.judgements.great() == 0
+    }
+}
+
+/// A cleared run as kept in a song's score table
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct HighScore {
+    score: u32,
+    accuracy: u8,
+    character: Character,
+    play: u16, // Play counter when the score was set, never 0
+    date: Option<Date>,
+}
+
+impl HighScore {
+    pub const PACKED_SIZE: usize = 8 + Date::PACKED_SIZE;
+
+    pub fn new(score: &Score, play: u16, date: Option<Date>) -> Self {
+        Self {
+            score: score.score().min(u32::MAX as usize) as u32,
+            accuracy: score.accuracy(),
+            character: score.character(),
+            play,
+            date,
+        }
+    }
+
+    /// Scores without a date store zero in its place, which is never a valid date
+    pub fn pack(&self) -> [u8; Self::PACKED_SIZE] {
+        let mut bytes = [0; Self::PACKED_SIZE];
+        bytes[0..4].copy_from_slice(&self.score.to_le_bytes());
+        bytes[4] = self.accuracy;
+        bytes[5] = self.character.to_u8();
+        bytes[6..8].copy_from_slice(&self.play.to_le_bytes());
+
+        if let Some(date) = self.date {
+            bytes[8..].copy_from_slice(&date.pack());
+        }
+
+        bytes
+    }
+
+    pub fn unpack(bytes: [u8; Self::PACKED_SIZE]) -> Option<Self> {
+        let accuracy = bytes[4];
+        let play = u16::from_le_bytes([bytes[6], bytes[7]]);
+
+        if accuracy > 100 || play == 0 {
+            return None;
+        }
+
+        let date = [bytes[8], bytes[9]];
+        let date = if date == [0; Date::PACKED_SIZE] {
+            None
+        } else {
+            Some(Date::unpack(date)?)
+        };
+
+        Some(Self {
+            score: u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+            accuracy,
+            character: Character::try_from(bytes[5]).ok()?,
+            play,
+            date,
+        })
+    }
+
+    pub fn score(&self) -> usize {
+        self.score as usize
+    }
+
+    pub fn grade(&self) -> Grade {
+        Grade::from_accuracy(self.accuracy)
+    }
+
+    pub fn play(&self) -> usize {
+        self.play as usize
+    }
+
+    pub fn date(&self) -> Option<Date> {
+        self.date
+    }
 }
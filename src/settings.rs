@@ -0,0 +1,54 @@
+use crate::sfx::MAX_VOLUME;
+
+#[derive(Debug, Clone, Copy, Hash)]
+pub struct Settings {
+    no_fail: bool,
+    sfx_volume: u8, // Out of MAX_VOLUME
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            no_fail: false,
+            sfx_volume: MAX_VOLUME,
+        }
+    }
+}
+
+impl Settings {
+    pub const PACKED_SIZE: usize = 1;
+
+    /// Packs no fail into the lowest bit, and the volume above it
+    pub fn pack(&self) -> [u8; Self::PACKED_SIZE] {
+        [self.no_fail as u8 | self.sfx_volume << 1]
+    }
+
+    pub fn unpack(bytes: [u8; Self::PACKED_SIZE]) -> Option<Self> {
+        let sfx_volume = bytes[0] >> 1;
+
+        if sfx_volume > MAX_VOLUME {
+            return None;
+        }
+
+        Some(Self {
+            no_fail: bytes[0] & 1 != 0,
+            sfx_volume,
+        })
+    }
+
+    pub fn no_fail(&self) -> bool {
+        self.no_fail
+    }
+
+    pub fn set_no_fail(&mut self, no_fail: bool) {
+        self.no_fail = no_fail;
+    }
+
+    pub fn sfx_volume(&self) -> u8 {
+        self.sfx_volume
+    }
+
+    pub fn set_sfx_volume(&mut self, sfx_volume: u8) {
+        self.sfx_volume = sfx_volume.min(MAX_VOLUME);
+    }
+}
@@ -0,0 +1,60 @@
+use agb::{
+    fixnum::Num,
+    include_wav,
+    sound::mixer::{Mixer, SoundChannel},
+};
+
+use crate::{score::Judgement, song_data::Track};
+
+pub const MAX_VOLUME: u8 = 10;
+
+const HIT_PERFECT_HIGH: &[u8] = include_wav!("assets/hit_perfect_high.wav");
+const HIT_PERFECT_LOW: &[u8] = include_wav!("assets/hit_perfect_low.wav");
+const HIT_GREAT_HIGH: &[u8] = include_wav!("assets/hit_great_high.wav");
+const HIT_GREAT_LOW: &[u8] = include_wav!("assets/hit_great_low.wav");
+const MISS: &[u8] = include_wav!("assets/miss.wav");
+const MENU_MOVE: &[u8] = include_wav!("assets/menu_move.wav");
+const MENU_SELECT: &[u8] = include_wav!("assets/menu_select.wav");
+const MENU_BACK: &[u8] = include_wav!("assets/menu_back.wav");
+const NEW_RECORD: &[u8] = include_wav!("assets/new_record.wav");
+
+#[derive(Debug, Clone, Copy)]
+pub enum Sfx {
+    Hit(Judgement, Track),
+    Miss,
+    MenuMove,
+    MenuSelect,
+    MenuBack,
+    NewRecord,
+}
+
+impl Sfx {
+    fn sound(self) -> &'static [u8] {
+        match self {
+            Sfx::Hit(Judgement::Perfect, Track::High) => HIT_PERFECT_HIGH,
+            Sfx::Hit(Judgement::Perfect, Track::Low) => HIT_PERFECT_LOW,
+            Sfx::Hit(Judgement::Great, Track::High) => HIT_GREAT_HIGH,
+            Sfx::Hit(Judgement::Great, Track::Low) => HIT_GREAT_LOW,
+            Sfx::Miss => MISS,
+            Sfx::MenuMove => MENU_MOVE,
+            Sfx::MenuSelect => MENU_SELECT,
+            Sfx::MenuBack => MENU_BACK,
+            Sfx::NewRecord => NEW_RECORD,
+        }
+    }
+}
+
+/// Plays a sound effect at a volume out of `MAX_VOLUME`. Effects use low
+/// priority channels, so they never take a channel from the music
+pub fn play(mixer: &mut Mixer, sfx: Sfx, volume: u8) {
+    if volume == 0 {
+        return;
+    }
+
+    let volume: Num<i16, 8> = Num::new(volume.min(MAX_VOLUME) as i16) / MAX_VOLUME as i16;
+
+    let mut channel = SoundChannel::new(sfx.sound());
+    channel.volume(volume);
+
+    mixer.play_sound(channel);
+}
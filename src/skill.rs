@@ -0,0 +1,84 @@
+/// A character's gameplay perk. Each method is given the normal value and
+/// returns the value to use, so skills only override what they change
+pub trait Skill {
+    fn name(&self) -> &'static str;
+
+    fn max_hp(&self, max_hp: usize) -> usize {
+        max_hp
+    }
+
+    /// How far from perfect a note can be and still be hit, in pixels
+    fn great_window(&self, window: i32) -> i32 {
+        window
+    }
+
+    fn hit_score(&self, score: usize) -> usize {
+        score
+    }
+
+    /// Whether hearts are collected from either track
+    fn collects_hearts(&self) -> bool {
+        false
+    }
+}
+
+/// Plays by the standard rules, for the default character so scores stay comparable
+pub struct NoSkill;
+
+impl Skill for NoSkill {
+    fn name(&self) -> &'static str {
+        "No skill: standard rules"
+    }
+}
+
+/// Wider window for greats
+pub struct Footwork;
+
+impl Skill for Footwork {
+    fn name(&self) -> &'static str {
+        "Footwork: wider hit window"
+    }
+
+    fn great_window(&self, window: i32) -> i32 {
+        window + 2
+    }
+}
+
+/// Extra HP
+pub struct Armour;
+
+impl Skill for Armour {
+    fn name(&self) -> &'static str {
+        "Armour: +50% HP"
+    }
+
+    fn max_hp(&self, max_hp: usize) -> usize {
+        max_hp * 3 / 2
+    }
+}
+
+/// Hearts are collected without needing to be on their track
+pub struct Scavenger;
+
+impl Skill for Scavenger {
+    fn name(&self) -> &'static str {
+        "Scavenger: grabs every heart"
+    }
+
+    fn collects_hearts(&self) -> bool {
+        true
+    }
+}
+
+/// Score multiplier for hits
+pub struct Glory;
+
+impl Skill for Glory {
+    fn name(&self) -> &'static str {
+        "Glory: +20% hit score"
+    }
+
+    fn hit_score(&self, score: usize) -> usize {
+        score * 6 / 5
+    }
+}
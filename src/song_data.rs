@@ -1,4 +1,6 @@
-#[derive(Debug, Clone, Copy)]
+use agb::display::{palette16::Palette16, tile_data::TileData};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Track {
     High,
     Low,
@@ -7,46 +9,192 @@ pub enum Track {
 pub enum Command {
     Note(Track),
     NoteBoth,
-    SetSpeed(i32),
+    Obstacle, // On the low track, jumped over
+    Heart(Track),
+    MusicNote(Track),
+    Ghost(Track), // Fades out before reaching the judgement area
+    GhostBoth,
+    BossEnter,
+    BossExit,
+    Throw(Track), // Thrown by the boss
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MotionType {
+    Constant,
+    Accelerate,
+    Decelerate,
+    Wave, // Constant speed, bobbing up and down until it reaches its track
+}
+
+/// How a note moves towards the judgement area
+#[derive(Debug, Clone, Copy)]
+pub struct Motion {
+    kind: MotionType,
+    travel: usize, // Frames from appearing to reaching the judgement area
+}
+
+impl Motion {
+    pub const fn new(kind: MotionType, travel: usize) -> Self {
+        Self { kind, travel }
+    }
+
+    pub fn kind(&self) -> MotionType {
+        self.kind
+    }
+
+    pub fn travel(&self) -> usize {
+        self.travel
+    }
+}
+
+/// Sprite tags a note is drawn with, while moving and once hit
+#[derive(Debug, Clone, Copy)]
+pub struct Skin {
+    tag: &'static str,
+    hit_tag: &'static str,
+}
+
+impl Skin {
+    pub const fn new(tag: &'static str, hit_tag: &'static str) -> Self {
+        Self { tag, hit_tag }
+    }
+
+    pub fn tag(&self) -> &'static str {
+        self.tag
+    }
+
+    pub fn hit_tag(&self) -> &'static str {
+        self.hit_tag
+    }
 }
 
 pub struct Fragment {
     command: Command,
     frame: usize, // Pontentially make this smaller?
+    motion: Motion,
+    skin: Skin,
+    sample: Option<&'static [u8]>, // Keysound played when the note is hit
 }
 
 impl Fragment {
-    pub const fn new(command: Command, frame: usize) -> Self {
-        Self { command, frame }
+    pub const fn new(
+        command: Command,
+        frame: usize,
+        motion: Motion,
+        skin: Skin,
+        sample: Option<&'static [u8]>,
+    ) -> Self {
+        Self {
+            command,
+            frame,
+            motion,
+            skin,
+            sample,
+        }
     }
 
     pub fn command(&self) -> &Command {
         &self.command
     }
 
+    pub fn motion(&self) -> Motion {
+        self.motion
+    }
+
+    pub fn skin(&self) -> Skin {
+        self.skin
+    }
+
+    pub fn sample(&self) -> Option<&'static [u8]> {
+        self.sample
+    }
+
     pub fn frame(&self) -> usize {
         self.frame
     }
 }
 
+/// Background drawn behind a song
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Theme {
+    Standard,
+    Night,
+    Sunset,
+}
+
+/// Background tiles and the palettes they were converted with
+#[derive(Clone, Copy)]
+pub struct Background {
+    layers: &'static TileData, // Sky, scenery and floor, laid out like the themes
+    tiles: &'static TileData,  // Shared tiles, such as the pause menu
+    palettes: &'static [Palette16],
+}
+
+impl Background {
+    pub const fn new(
+        layers: &'static TileData,
+        tiles: &'static TileData,
+        palettes: &'static [Palette16],
+    ) -> Self {
+        Self {
+            layers,
+            tiles,
+            palettes,
+        }
+    }
+
+    pub fn layers(&self) -> &'static TileData {
+        self.layers
+    }
+
+    pub fn tiles(&self) -> &'static TileData {
+        self.tiles
+    }
+
+    pub fn palettes(&self) -> &'static [Palette16] {
+        self.palettes
+    }
+}
+
 pub trait SongDataTrait {
     fn name(&self) -> &'static str;
+    fn notes(&self) -> usize;
     fn sound(&self) -> &[u8];
+    fn lead_in(&self) -> usize;
     fn fragments(&self) -> &[Fragment];
+    fn theme(&self) -> Theme;
+    fn background(&self) -> Option<&'static Background>;
 }
 
 pub struct SongData<const N: usize> {
     name: &'static str,
+    notes: usize, // Hittable notes, a fragment can spawn more than one
     fragments: [Fragment; N],
     sound: &'static [u8],
+    lead_in: usize, // Frames before the music starts, for notes that spawn before it
+    theme: Theme,
+    background: Option<&'static Background>, // Replaces the theme when set
 }
 
 impl<const N: usize> SongData<N> {
-    pub const fn new(name: &'static str, fragments: [Fragment; N], sound: &'static [u8]) -> Self {
+    pub const fn new(
+        name: &'static str,
+        notes: usize,
+        fragments: [Fragment; N],
+        sound: &'static [u8],
+        lead_in: usize,
+        theme: Theme,
+        background: Option<&'static Background>,
+    ) -> Self {
         Self {
             name,
+            notes,
             fragments,
             sound,
+            lead_in,
+            theme,
+            background,
         }
     }
 }
@@ -56,11 +204,27 @@ impl<const N: usize> SongDataTrait for SongData<N> {
         self.name
     }
 
+    fn notes(&self) -> usize {
+        self.notes
+    }
+
     fn sound(&self) -> &[u8] {
         self.sound
     }
 
+    fn lead_in(&self) -> usize {
+        self.lead_in
+    }
+
     fn fragments(&self) -> &[Fragment] {
         &self.fragments
     }
+
+    fn theme(&self) -> Theme {
+        self.theme
+    }
+
+    fn background(&self) -> Option<&'static Background> {
+        self.background
+    }
 }
@@ -1,6 +1,6 @@
 #![allow(non_snake_case)]
 
-use crate::song_data::Fragment;
+use crate::song_data::{Background, Fragment, Theme};
 
 #[derive(Debug, Clone, Copy)]
 pub struct SongID(usize);
@@ -14,13 +14,29 @@ impl SongID {
         SONGS[self.0].name()
     }
 
+    pub fn notes(&self) -> usize {
+        SONGS[self.0].notes()
+    }
+
     pub fn sound(&self) -> &'static [u8] {
         SONGS[self.0].sound()
     }
 
+    pub fn lead_in(&self) -> usize {
+        SONGS[self.0].lead_in()
+    }
+
     pub fn fragments(&self) -> &'static [Fragment] {
         SONGS[self.0].fragments()
     }
+
+    pub fn theme(&self) -> Theme {
+        SONGS[self.0].theme()
+    }
+
+    pub fn background(&self) -> Option<&'static Background> {
+        SONGS[self.0].background()
+    }
 }
 
 impl From<usize> for SongID {
@@ -0,0 +1,188 @@
+use core::fmt::Write;
+
+use agb::{
+    display::{
+        font::TextRenderer,
+        object::{OamManaged, Object},
+        tiled::{
+            MapLoan, RegularBackgroundSize, RegularMap, TileFormat, Tiled0, TiledMap, VRamManager,
+        },
+        Priority,
+    },
+    include_background_gfx,
+    input::{Button, ButtonController},
+    sound::mixer::Mixer,
+};
+
+use crate::{
+    character::{Animation, Character},
+    save_data::SaveDataManager,
+    sfx::{self, Sfx},
+    FONT,
+};
+
+use super::{song::GRAPHICS, Callback, State};
+
+include_background_gfx!(background, tiles => "assets/menu_tiles.aseprite");
+
+// Preview of the character running, in the middle of the screen
+const PREVIEW_X: i32 = (agb::display::WIDTH - 64) / 2;
+const PREVIEW_Y: i32 = 56;
+const PREVIEW_SPEED: usize = 5;
+
+pub struct CharacterSelectState<'a, 'b> {
+    bg: Option<MapLoan<'b, RegularMap>>,
+    text: Option<(MapLoan<'b, RegularMap>, TextRenderer<'b>)>,
+    preview: Object<'a>,
+    character: Character,
+    frame: usize,
+}
+
+impl<'a, 'b> CharacterSelectState<'a, 'b> {
+    pub fn new(object_gfx: &'a OamManaged) -> Self {
+        let character = Character::default();
+        let tag = character.tag(&Animation::Running, false);
+        let mut preview = object_gfx.object_sprite(GRAPHICS.get(tag).sprite(0));
+        preview.set_position((PREVIEW_X, PREVIEW_Y).into());
+        preview.show();
+
+        Self {
+            bg: None,
+            text: None,
+            preview,
+            character,
+            frame: 0,
+        }
+    }
+
+    fn redraw(&mut self, save_data: &mut SaveDataManager, vram: &mut VRamManager) {
+        if let Some((text, renderer)) = &mut self.text {
+            text.clear(vram);
+            renderer.clear(vram);
+
+            let mut writer = renderer.writer(3, 0, text, vram);
+
+            write!(writer, "Character:\n< {} >", self.character.name()).unwrap();
+
+            if self.character == save_data.get_profile().character() {
+                write!(writer, " (current)").unwrap();
+            }
+
+            write!(
+                writer,
+                "\n\n\n\n\n\n\n{}\nA: select  B: back",
+                self.character.skill().name()
+            )
+            .unwrap();
+
+            writer.commit();
+
+            text.commit(vram);
+            text.show();
+        }
+    }
+}
+
+impl<'a, 'b> State<'a, 'b> for CharacterSelectState<'a, 'b> {
+    fn init(
+        &mut self,
+        save_data: &mut SaveDataManager,
+        _object_gfx: &'a OamManaged,
+        tiled0: &'b Tiled0<'b>,
+        vram: &mut VRamManager,
+        _mixer: &mut Mixer,
+    ) {
+        // Background
+        vram.set_background_palettes(background::PALETTES);
+
+        let mut bg = tiled0.background(
+            Priority::P3,
+            RegularBackgroundSize::Background32x32,
+            TileFormat::FourBpp,
+        );
+
+        let text = tiled0.background(
+            Priority::P1,
+            RegularBackgroundSize::Background32x32,
+            TileFormat::FourBpp,
+        );
+
+        for y in 0..20u16 {
+            for x in 0..32u16 {
+                let tile_id = if y == 0 {
+                    0
+                } else if y == 1 {
+                    1
+                } else {
+                    2
+                };
+
+                bg.set_tile(
+                    vram,
+                    (x, y).into(),
+                    &background::tiles.tiles,
+                    background::tiles.tile_settings[tile_id],
+                );
+            }
+        }
+
+        bg.commit(vram);
+        bg.show();
+
+        self.bg = Some(bg);
+
+        let renderer = FONT.render_text((3u16, 0u16).into());
+
+        self.text = Some((text, renderer));
+
+        self.character = save_data.get_profile().character();
+
+        self.redraw(save_data, vram);
+    }
+
+    fn update(
+        &mut self,
+        save_data: &mut SaveDataManager,
+        object_gfx: &'a OamManaged,
+        vram: &mut VRamManager,
+        mixer: &mut Mixer,
+        input: &ButtonController,
+    ) -> Callback {
+        let volume = save_data.get_settings().sfx_volume();
+
+        if input.is_just_pressed(Button::LEFT) {
+            self.character = self.character.previous();
+            sfx::play(mixer, Sfx::MenuMove, volume);
+            self.redraw(save_data, vram);
+        }
+
+        if input.is_just_pressed(Button::RIGHT) {
+            self.character = self.character.next();
+            sfx::play(mixer, Sfx::MenuMove, volume);
+            self.redraw(save_data, vram);
+        }
+
+        self.frame += 1;
+
+        let tag = self.character.tag(&Animation::Running, false);
+        let sprite = GRAPHICS
+            .get(tag)
+            .animation_sprite(self.frame / PREVIEW_SPEED);
+        self.preview.set_sprite(object_gfx.sprite(sprite));
+
+        if let Some(bg) = &mut self.bg {
+            bg.commit(vram);
+        }
+
+        if input.is_just_pressed(Button::A) {
+            sfx::play(mixer, Sfx::MenuSelect, volume);
+            save_data.set_character(self.character);
+            Callback::SetState(super::SetState::MainMenu)
+        } else if input.is_just_pressed(Button::B) {
+            sfx::play(mixer, Sfx::MenuBack, volume);
+            Callback::SetState(super::SetState::MainMenu)
+        } else {
+            Callback::None
+        }
+    }
+}
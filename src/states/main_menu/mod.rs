@@ -5,7 +5,7 @@ use agb::{
         font::TextRenderer,
         object::{OamManaged, Object, TagMap},
         tiled::{
-            MapLoan, RegularBackgroundSize, RegularMap, TileFormat, Tiled1, TiledMap, VRamManager,
+            MapLoan, RegularBackgroundSize, RegularMap, TileFormat, Tiled0, TiledMap, VRamManager,
         },
         Priority,
     },
@@ -14,7 +14,11 @@ use agb::{
     sound::mixer::Mixer,
 };
 
-use crate::{save_data::SaveDataManager, FONT};
+use crate::{
+    save_data::SaveDataManager,
+    sfx::{self, Sfx},
+    FONT,
+};
 
 use super::{Callback, State};
 
@@ -22,7 +26,7 @@ include_background_gfx!(background, tiles => "assets/menu_tiles.aseprite");
 
 const GRAPHICS: &TagMap = include_aseprite!("assets/menu_selector.aseprite").tags();
 
-const OPTIONS: [&str; 2] = ["Play", "Reset data"];
+const OPTIONS: [&str; 5] = ["Play", "Character", "Profile", "Settings", "Reset data"];
 
 pub struct MainMenuState<'a, 'b> {
     bg: Option<MapLoan<'b, RegularMap>>,
@@ -52,20 +56,20 @@ impl<'a, 'b> State<'a, 'b> for MainMenuState<'a, 'b> {
         &mut self,
         _save_data: &mut SaveDataManager,
         _object_gfx: &'a OamManaged,
-        tiled1: &'b Tiled1<'b>,
+        tiled0: &'b Tiled0<'b>,
         vram: &mut VRamManager,
         _mixer: &mut Mixer,
     ) {
         // Background
         vram.set_background_palettes(background::PALETTES);
 
-        let mut bg = tiled1.regular(
+        let mut bg = tiled0.background(
             Priority::P3,
             RegularBackgroundSize::Background32x32,
             TileFormat::FourBpp,
         );
 
-        let mut text = tiled1.regular(
+        let mut text = tiled0.background(
             Priority::P1,
             RegularBackgroundSize::Background32x32,
             TileFormat::FourBpp,
@@ -116,15 +120,19 @@ impl<'a, 'b> State<'a, 'b> for MainMenuState<'a, 'b> {
         save_data: &mut SaveDataManager,
         _object_gfx: &'a OamManaged,
         vram: &mut VRamManager,
-        _mixer: &mut Mixer,
+        mixer: &mut Mixer,
         input: &ButtonController,
     ) -> Callback {
+        let volume = save_data.get_settings().sfx_volume();
+
         if input.is_just_pressed(Button::UP) && self.current_option > 0 {
             self.current_option -= 1;
+            sfx::play(mixer, Sfx::MenuMove, volume);
         }
 
-        if input.is_just_pressed(Button::DOWN) && self.current_option < OPTIONS.len() {
+        if input.is_just_pressed(Button::DOWN) && self.current_option < OPTIONS.len() - 1 {
             self.current_option += 1;
+            sfx::play(mixer, Sfx::MenuMove, volume);
         }
 
         let y = ((self.current_option + 1) * 14) - 1;
@@ -139,9 +147,14 @@ impl<'a, 'b> State<'a, 'b> for MainMenuState<'a, 'b> {
         }
 
         if input.is_just_pressed(Button::A) || input.is_just_pressed(Button::START) {
+            sfx::play(mixer, Sfx::MenuSelect, volume);
+
             match self.current_option {
                 0 => Callback::SetState(super::SetState::SongMenu),
-                1 => {
+                1 => Callback::SetState(super::SetState::CharacterSelect),
+                2 => Callback::SetState(super::SetState::Profile),
+                3 => Callback::SetState(super::SetState::Settings),
+                4 => {
                     save_data.reset();
                     Callback::None
                 }
@@ -3,20 +3,29 @@ use crate::{save_data::SaveDataManager, score::Score, songs::SongID};
 use agb::{
     display::{
         object::OamManaged,
-        tiled::{Tiled1, VRamManager},
+        tiled::{Tiled0, VRamManager},
     },
     input::ButtonController,
     sound::mixer::Mixer,
 };
+use alloc::boxed::Box;
 
+pub use character_select::CharacterSelectState;
 pub use main_menu::MainMenuState;
+pub use profile::ProfileState;
 pub use result_screen::ResultState;
+pub use save_warning::SaveWarningState;
+pub use settings::SettingsState;
 pub use song::SongState;
 pub use song_info::SongInfoState;
 pub use song_menu::SongMenuState;
 
+mod character_select;
 mod main_menu;
+mod profile;
 mod result_screen;
+mod save_warning;
+mod settings;
 mod song;
 mod song_info;
 mod song_menu;
@@ -26,7 +35,11 @@ pub enum SetState {
     SongInfo(SongID),
     SongMenu,
     MainMenu,
+    Profile,
+    CharacterSelect,
+    Settings,
     ResultScreen(SongID, Score),
+    SaveWarning(Box<SetState>), // Shown before continuing to the given state
 }
 
 pub enum Callback {
@@ -39,7 +52,7 @@ pub trait State<'a, 'b> {
         &mut self,
         save_data: &mut SaveDataManager,
         object_gfx: &'a OamManaged<'a>,
-        tiled1: &'b Tiled1<'b>,
+        tiled0: &'b Tiled0<'b>,
         vram: &mut VRamManager,
         mixer: &mut Mixer,
     );
@@ -0,0 +1,323 @@
+use core::{fmt::Write, marker::PhantomData};
+
+use agb::{
+    display::{
+        font::TextRenderer,
+        object::{OamManaged, Object},
+        tiled::{
+            MapLoan, RegularBackgroundSize, RegularMap, TileFormat, Tiled0, TiledMap, VRamManager,
+        },
+        Priority,
+    },
+    include_background_gfx,
+    input::{Button, ButtonController},
+    sound::mixer::Mixer,
+};
+
+use crate::{
+    save_data::SaveDataManager,
+    score::{GRADES, GRADES_COUNT},
+    sfx::{self, Sfx},
+    songs::{SongID, SONGS_COUNT},
+    stats::NAME_LENGTH,
+    FONT,
+};
+
+use super::{Callback, State};
+
+include_background_gfx!(background, tiles => "assets/menu_tiles.aseprite");
+
+const KEYBOARD_WIDTH: usize = 10;
+const KEYBOARD: [&[u8; KEYBOARD_WIDTH]; 4] =
+    [b"ABCDEFGHIJ", b"KLMNOPQRST", b"UVWXYZ0123", b"456789-.! "];
+
+struct Keyboard {
+    name: [u8; NAME_LENGTH],
+    len: usize,
+    x: usize,
+    y: usize,
+}
+
+impl Keyboard {
+    fn new(name: &str) -> Self {
+        let mut keyboard = Self {
+            name: [0; NAME_LENGTH],
+            len: 0,
+            x: 0,
+            y: 0,
+        };
+
+        for c in name.bytes() {
+            keyboard.push(c);
+        }
+
+        keyboard
+    }
+
+    fn push(&mut self, c: u8) {
+        if self.len < NAME_LENGTH {
+            self.name[self.len] = c;
+            self.len += 1;
+        }
+    }
+
+    fn pop(&mut self) {
+        if self.len > 0 {
+            self.len -= 1;
+        }
+    }
+
+    fn selected(&self) -> u8 {
+        KEYBOARD[self.y][self.x]
+    }
+
+    fn name(&self) -> &[u8] {
+        &self.name[..self.len]
+    }
+}
+
+pub struct ProfileState<'a, 'b> {
+    bg: Option<MapLoan<'b, RegularMap>>,
+    text: Option<(MapLoan<'b, RegularMap>, TextRenderer<'b>)>,
+    keyboard: Option<Keyboard>, // Some while editing the name
+
+    _data: PhantomData<Object<'a>>,
+}
+
+impl<'a, 'b> ProfileState<'a, 'b> {
+    pub fn new() -> Self {
+        Self {
+            bg: None,
+            text: None,
+            keyboard: None,
+
+            _data: PhantomData,
+        }
+    }
+
+    fn redraw(&mut self, save_data: &mut SaveDataManager, vram: &mut VRamManager) {
+        if let Some((text, renderer)) = &mut self.text {
+            text.clear(vram);
+            renderer.clear(vram);
+
+            if let Some(keyboard) = &self.keyboard {
+                let mut writer = renderer.writer(3, 0, text, vram);
+
+                writeln!(
+                    writer,
+                    "Name: {}_\n",
+                    core::str::from_utf8(keyboard.name()).unwrap_or("")
+                )
+                .unwrap();
+
+                writer.commit();
+
+                for (y, row) in KEYBOARD.iter().enumerate() {
+                    for (x, c) in row.iter().enumerate() {
+                        let color = if x == keyboard.x && y == keyboard.y {
+                            9
+                        } else {
+                            3
+                        };
+
+                        // Show space as an underscore so it can be selected
+                        let c = if *c == b' ' { '_' } else { *c as char };
+
+                        let mut writer = renderer.writer(color, 0, text, vram);
+                        write!(writer, "{} ", c).unwrap();
+                        writer.commit();
+                    }
+
+                    let mut writer = renderer.writer(3, 0, text, vram);
+                    writeln!(writer).unwrap();
+                    writer.commit();
+                }
+
+                let mut writer = renderer.writer(3, 0, text, vram);
+                write!(writer, "\nA: type  B: delete\nSTART: done").unwrap();
+                writer.commit();
+            } else {
+                let profile = save_data.get_profile();
+
+                let mut plays = 0;
+                let mut notes_hit = 0;
+                let mut best_combo = 0;
+                let mut grades = [0; GRADES_COUNT];
+
+                for song in 0..SONGS_COUNT {
+                    let stats = save_data.get_stats(SongID::new(song));
+
+                    plays += stats.plays();
+                    notes_hit += stats.notes_hit();
+                    best_combo = best_combo.max(stats.best_max_combo());
+
+                    if let Some(grade) = stats.best_grade() {
+                        grades[grade as usize] += 1;
+                    }
+                }
+
+                let seconds = profile.play_time() / 60;
+
+                let mut writer = renderer.writer(3, 0, text, vram);
+
+                write!(
+                    writer,
+                    "Profile: {}\nPlays: {}\nNotes hit: {}\nPlay time: {}:{:02}:{:02}\nBest combo: {}\n",
+                    profile.name(),
+                    plays,
+                    notes_hit,
+                    seconds / 3600,
+                    (seconds / 60) % 60,
+                    seconds % 60,
+                    best_combo
+                )
+                .unwrap();
+
+                for (i, grade) in GRADES.iter().enumerate() {
+                    write!(writer, "{} {}  ", grade.to_str(), grades[*grade as usize]).unwrap();
+
+                    if i == 3 {
+                        writeln!(writer).unwrap();
+                    }
+                }
+
+                write!(writer, "\nA: edit name  B: back").unwrap();
+
+                writer.commit();
+            }
+
+            text.commit(vram);
+            text.show();
+        }
+    }
+}
+
+impl<'a, 'b> State<'a, 'b> for ProfileState<'a, 'b> {
+    fn init(
+        &mut self,
+        save_data: &mut SaveDataManager,
+        _object_gfx: &'a OamManaged,
+        tiled0: &'b Tiled0<'b>,
+        vram: &mut VRamManager,
+        _mixer: &mut Mixer,
+    ) {
+        // Background
+        vram.set_background_palettes(background::PALETTES);
+
+        let mut bg = tiled0.background(
+            Priority::P3,
+            RegularBackgroundSize::Background32x32,
+            TileFormat::FourBpp,
+        );
+
+        let text = tiled0.background(
+            Priority::P1,
+            RegularBackgroundSize::Background32x32,
+            TileFormat::FourBpp,
+        );
+
+        for y in 0..20u16 {
+            for x in 0..32u16 {
+                let tile_id = if y == 0 {
+                    0
+                } else if y == 1 {
+                    1
+                } else {
+                    2
+                };
+
+                bg.set_tile(
+                    vram,
+                    (x, y).into(),
+                    &background::tiles.tiles,
+                    background::tiles.tile_settings[tile_id],
+                );
+            }
+        }
+
+        bg.commit(vram);
+        bg.show();
+
+        self.bg = Some(bg);
+
+        let renderer = FONT.render_text((3u16, 0u16).into());
+
+        self.text = Some((text, renderer));
+
+        self.redraw(save_data, vram);
+    }
+
+    fn update(
+        &mut self,
+        save_data: &mut SaveDataManager,
+        _object_gfx: &'a OamManaged,
+        vram: &mut VRamManager,
+        mixer: &mut Mixer,
+        input: &ButtonController,
+    ) -> Callback {
+        let volume = save_data.get_settings().sfx_volume();
+        let mut redraw = false;
+
+        if let Some(keyboard) = &mut self.keyboard {
+            if input.is_just_pressed(Button::LEFT) {
+                keyboard.x = (keyboard.x + KEYBOARD_WIDTH - 1) % KEYBOARD_WIDTH;
+                sfx::play(mixer, Sfx::MenuMove, volume);
+                redraw = true;
+            }
+
+            if input.is_just_pressed(Button::RIGHT) {
+                keyboard.x = (keyboard.x + 1) % KEYBOARD_WIDTH;
+                sfx::play(mixer, Sfx::MenuMove, volume);
+                redraw = true;
+            }
+
+            if input.is_just_pressed(Button::UP) {
+                keyboard.y = (keyboard.y + KEYBOARD.len() - 1) % KEYBOARD.len();
+                sfx::play(mixer, Sfx::MenuMove, volume);
+                redraw = true;
+            }
+
+            if input.is_just_pressed(Button::DOWN) {
+                keyboard.y = (keyboard.y + 1) % KEYBOARD.len();
+                sfx::play(mixer, Sfx::MenuMove, volume);
+                redraw = true;
+            }
+
+            if input.is_just_pressed(Button::A) {
+                keyboard.push(keyboard.selected());
+                sfx::play(mixer, Sfx::MenuSelect, volume);
+                redraw = true;
+            }
+
+            if input.is_just_pressed(Button::B) {
+                keyboard.pop();
+                sfx::play(mixer, Sfx::MenuBack, volume);
+                redraw = true;
+            }
+
+            if input.is_just_pressed(Button::START) {
+                save_data.set_name(keyboard.name());
+                sfx::play(mixer, Sfx::MenuSelect, volume);
+                self.keyboard = None;
+                redraw = true;
+            }
+        } else if input.is_just_pressed(Button::A) {
+            self.keyboard = Some(Keyboard::new(save_data.get_profile().name()));
+            sfx::play(mixer, Sfx::MenuSelect, volume);
+            redraw = true;
+        } else if input.is_just_pressed(Button::B) {
+            sfx::play(mixer, Sfx::MenuBack, volume);
+            return Callback::SetState(super::SetState::MainMenu);
+        }
+
+        if redraw {
+            self.redraw(save_data, vram);
+        }
+
+        if let Some(bg) = &mut self.bg {
+            bg.commit(vram);
+        }
+
+        Callback::None
+    }
+}
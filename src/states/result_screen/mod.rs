@@ -2,12 +2,15 @@ use core::fmt::Write;
 
 use agb::{
     display::{
-        object::{OamManaged, Object, TagMap},
+        affine::AffineMatrix,
+        font::TextRenderer,
+        object::{AffineMatrixInstance, AffineMode, OamManaged, Object, TagMap},
         tiled::{
-            MapLoan, RegularBackgroundSize, RegularMap, TileFormat, Tiled1, TiledMap, VRamManager,
+            MapLoan, RegularBackgroundSize, RegularMap, TileFormat, Tiled0, TiledMap, VRamManager,
         },
         Priority,
     },
+    fixnum::{Num, Vector2D},
     include_aseprite, include_background_gfx,
     input::{Button, ButtonController},
     sound::mixer::Mixer,
@@ -15,7 +18,8 @@ use agb::{
 
 use crate::{
     save_data::SaveDataManager,
-    score::{Grade, Score},
+    score::{Grade, HighScore, Score},
+    sfx::{self, Sfx},
     songs::SongID,
     FONT,
 };
@@ -26,13 +30,30 @@ include_background_gfx!(background, tiles => "assets/result_tiles.aseprite");
 
 const GRAPHICS: &TagMap = include_aseprite!("assets/grades.aseprite").tags();
 
+// Reveal timings, in frames
+const SCORE_COUNT_LENGTH: usize = 60;
+const MAX_COMBO_FRAME: usize = 75;
+const ACCURACY_FRAME: usize = 90;
+const GRADE_FRAME: usize = 105;
+const GRADE_DROP_LENGTH: usize = 20;
+const SCORES_FRAME: usize = GRADE_FRAME + GRADE_DROP_LENGTH + 15;
+
+const GRADE_X: i32 = 150;
+const GRADE_Y: i32 = 66;
+const GRADE_SIZE: i32 = 64;
+const GRADE_DROP_HEIGHT: i32 = 80;
+
 pub struct ResultState<'a, 'b> {
     song_id: SongID,
     score: Score,
+    previous_best: Option<HighScore>,
+    rank: Option<usize>,
+    frame: usize,
+    show_breakdown: bool, // Instead of the score table
 
     bg: Option<MapLoan<'b, RegularMap>>,
-    text: Option<MapLoan<'b, RegularMap>>,
-    _grade_object: Object<'a>,
+    text: Option<(MapLoan<'b, RegularMap>, TextRenderer<'b>)>,
+    grade_object: Object<'a>,
 }
 
 impl<'a, 'b> ResultState<'a, 'b> {
@@ -40,20 +61,203 @@ impl<'a, 'b> ResultState<'a, 'b> {
         let grade = score.grade();
         let grade_str = grade.to_str();
 
+        // Hidden until the grade is revealed
         let sprite = GRAPHICS.get(grade_str).sprite(0);
-        let mut grade_object = object_gfx.object_sprite(sprite);
-        grade_object.show();
-        grade_object.set_position((150, 66).into());
+        let grade_object = object_gfx.object_sprite(sprite);
 
         Self {
             song_id,
             score,
+            previous_best: None,
+            rank: None,
+            frame: 0,
+            show_breakdown: false,
 
             bg: None,
             text: None,
-            _grade_object: grade_object,
+            grade_object,
         }
     }
+
+    fn new_record(&self) -> bool {
+        self.rank == Some(0)
+    }
+
+    fn redraw(&mut self, save_data: &mut SaveDataManager, vram: &mut VRamManager) {
+        let new_record = self.new_record();
+
+        if let Some((text, renderer)) = &mut self.text {
+            text.clear(vram);
+            renderer.clear(vram);
+
+            {
+                let mut writer = renderer.writer(10, 0, text, vram);
+
+                // Count the score up, only showing the difference once it's done
+                let counted =
+                    self.score.score() * self.frame.min(SCORE_COUNT_LENGTH) / SCORE_COUNT_LENGTH;
+
+                let title = if self.score.cleared() {
+                    "Results"
+                } else {
+                    "FAILED"
+                };
+
+                write!(
+                    writer,
+                    " {} - {}\n  Score: {}",
+                    title,
+                    self.song_id.name(),
+                    counted
+                )
+                .unwrap();
+
+                // Failed runs aren't compared against the best score
+                let cleared = self.score.cleared();
+
+                if let Some(previous_best) = self.previous_best.filter(|_| cleared) {
+                    if self.frame >= SCORE_COUNT_LENGTH {
+                        let delta = self.score.score() as isize - previous_best.score() as isize;
+                        write!(writer, " ({:+})", delta).unwrap();
+                    }
+                }
+
+                writeln!(writer).unwrap();
+
+                if self.frame >= MAX_COMBO_FRAME {
+                    write!(writer, "  Max combo: {}", self.score.max_combo()).unwrap();
+                }
+
+                writeln!(writer).unwrap();
+
+                if self.frame >= ACCURACY_FRAME {
+                    write!(writer, "  Accuracy: {}%", self.score.accuracy()).unwrap();
+                }
+
+                writeln!(writer).unwrap();
+
+                if self.frame >= SCORES_FRAME {
+                    if self.show_breakdown {
+                        let judgements = self.score.judgements();
+
+                        write!(writer, "\n Breakdown:").unwrap();
+
+                        for (name, track) in
+                            [("High", judgements.high()), ("Low", judgements.low())]
+                        {
+                            write!(
+                                writer,
+                                "\n  {}: {} P  {} G  {} M",
+                                name,
+                                track.perfect(),
+                                track.great(),
+                                track.miss()
+                            )
+                            .unwrap();
+                        }
+
+                        write!(
+                            writer,
+                            "\n  Early: {}  Late: {}",
+                            judgements.early(),
+                            judgements.late()
+                        )
+                        .unwrap();
+
+                        write!(writer, "\n  Character: {}", self.score.character().name()).unwrap();
+                    } else {
+                        write!(writer, "\n Scores:",).unwrap();
+                    }
+                }
+
+                writer.commit();
+            }
+
+            if self.frame >= SCORES_FRAME && !self.show_breakdown {
+                if !save_data.saving() {
+                    let mut writer = renderer.writer(3, 0, text, vram);
+                    write!(writer, " not saved!").unwrap();
+                    writer.commit();
+                } else if new_record {
+                    let mut writer = renderer.writer(9, 0, text, vram);
+                    write!(writer, " New best!").unwrap();
+                    writer.commit();
+                }
+
+                for (i, score) in save_data
+                    .get_scores(self.song_id)
+                    .into_iter()
+                    .enumerate()
+                    .filter_map(|(i, score)| Some((i, score?)))
+                {
+                    let color = match score.grade() {
+                        Grade::SSS => 9,
+                        Grade::SS => 8,
+                        Grade::S => 7,
+                        Grade::A => 6,
+                        Grade::B => 5,
+                        Grade::C => 4,
+                        Grade::D => 3,
+                    };
+
+                    // Highlight where this run landed
+                    let (marker, score_color) = if self.rank == Some(i) {
+                        ('>', 9)
+                    } else {
+                        (' ', 10)
+                    };
+
+                    let mut writer = renderer.writer(color, 0, text, vram);
+                    write!(writer, "\n {}{}", marker, score.grade().to_print_str()).unwrap();
+                    writer.commit();
+
+                    let mut writer = renderer.writer(score_color, 0, text, vram);
+                    write!(writer, " - {}", score.score()).unwrap();
+                    writer.commit();
+
+                    let mut writer = renderer.writer(3, 0, text, vram);
+                    if let Some(date) = score.date() {
+                        write!(writer, "  {}", date).unwrap();
+                    } else {
+                        write!(writer, "  #{}", score.play()).unwrap();
+                    }
+                    writer.commit();
+                }
+            }
+
+            text.commit(vram);
+            text.show();
+        }
+    }
+
+    fn update_grade(&mut self) {
+        // Failed runs don't get a grade
+        if self.frame < GRADE_FRAME || !self.score.cleared() {
+            return;
+        }
+
+        let remaining = GRADE_DROP_LENGTH - (self.frame - GRADE_FRAME).min(GRADE_DROP_LENGTH);
+
+        if remaining == 0 {
+            self.grade_object.set_position((GRADE_X, GRADE_Y).into());
+            self.grade_object.show();
+            return;
+        }
+
+        // Shrink from double size while dropping into place. The matrix maps
+        // screen to sprite space, so it takes the inverse of the scale
+        let inverse_scale: Num<i32, 8> =
+            Num::new(GRADE_DROP_LENGTH as i32) / (GRADE_DROP_LENGTH + remaining) as i32;
+        let matrix = AffineMatrix::from_scale(Vector2D::new(inverse_scale, inverse_scale));
+
+        let y = GRADE_Y - GRADE_DROP_HEIGHT * remaining as i32 / GRADE_DROP_LENGTH as i32;
+
+        // Double size objects are drawn centred in a box twice as large
+        self.grade_object
+            .set_affine_matrix(AffineMatrixInstance::new(matrix.to_object_wrapping()))
+            .set_position((GRADE_X - GRADE_SIZE / 2, y - GRADE_SIZE / 2).into())
+            .show_affine(AffineMode::AffineDouble);
+    }
 }
 
 impl<'a, 'b> State<'a, 'b> for ResultState<'a, 'b> {
@@ -61,20 +265,20 @@ impl<'a, 'b> State<'a, 'b> for ResultState<'a, 'b> {
         &mut self,
         save_data: &mut SaveDataManager,
         _object_gfx: &'a OamManaged,
-        tiled1: &'b Tiled1<'b>,
+        tiled0: &'b Tiled0<'b>,
         vram: &mut VRamManager,
         _mixer: &mut Mixer,
     ) {
         // Background
         vram.set_background_palettes(background::PALETTES);
 
-        let mut bg = tiled1.regular(
+        let mut bg = tiled0.background(
             Priority::P3,
             RegularBackgroundSize::Background32x32,
             TileFormat::FourBpp,
         );
 
-        let mut text = tiled1.regular(
+        let text = tiled0.background(
             Priority::P1,
             RegularBackgroundSize::Background32x32,
             TileFormat::FourBpp,
@@ -98,66 +302,68 @@ impl<'a, 'b> State<'a, 'b> for ResultState<'a, 'b> {
 
         self.bg = Some(bg);
 
-        // SAVE TO DISK
-        save_data.insert_score(self.song_id, self.score);
+        self.previous_best = save_data.get_scores(self.song_id)[0];
 
-        let mut renderer = FONT.render_text((0u16, 0u16).into());
-        {
-            let mut writer = renderer.writer(10, 0, &mut text, vram);
-
-            write!(
-                writer,
-                " Results - {}\n  Score: {}\n  Max combo: {}\n  Accuracy: {}%\n",
-                self.song_id.name(),
-                self.score.score(),
-                self.score.max_combo(),
-                self.score.accuracy()
-            )
-            .unwrap();
-
-            write!(writer, "\n Scores:",).unwrap();
-
-            writer.commit();
-        }
+        // SAVE TO DISK
+        self.rank = save_data.insert_score(self.song_id, self.score);
 
-        for score in save_data.get_scores(self.song_id).into_iter().flatten() {
-            let color = match score.grade() {
-                Grade::SSS => 9,
-                Grade::SS => 8,
-                Grade::S => 7,
-                Grade::A => 6,
-                Grade::B => 5,
-                Grade::C => 4,
-                Grade::D => 3,
-            };
-
-            let mut writer = renderer.writer(color, 0, &mut text, vram);
-            write!(writer, "\n  {}", score.grade().to_print_str()).unwrap();
-            writer.commit();
-
-            let mut writer = renderer.writer(10, 0, &mut text, vram);
-            write!(writer, " - {}", score.score()).unwrap();
-            writer.commit();
-        }
+        let renderer = FONT.render_text((0u16, 0u16).into());
 
-        text.commit(vram);
-        text.show();
+        self.text = Some((text, renderer));
 
-        self.text = Some(text);
+        self.redraw(save_data, vram);
     }
 
     fn update(
         &mut self,
-        _save_data: &mut SaveDataManager,
+        save_data: &mut SaveDataManager,
         _object_gfx: &'a OamManaged,
-        _vram: &mut VRamManager,
-        _mixer: &mut Mixer,
+        vram: &mut VRamManager,
+        mixer: &mut Mixer,
         input: &ButtonController,
     ) -> Callback {
-        if input.is_just_pressed(Button::A) || input.is_just_pressed(Button::START) {
-            Callback::SetState(super::SetState::SongMenu)
+        let pressed = input.is_just_pressed(Button::A) || input.is_just_pressed(Button::START);
+
+        if self.frame >= SCORES_FRAME {
+            if pressed {
+                sfx::play(
+                    mixer,
+                    Sfx::MenuSelect,
+                    save_data.get_settings().sfx_volume(),
+                );
+                return Callback::SetState(super::SetState::SongMenu);
+            }
+
+            // Switch between the score table and judgement breakdown
+            if input.is_just_pressed(Button::LEFT) || input.is_just_pressed(Button::RIGHT) {
+                self.show_breakdown = !self.show_breakdown;
+                self.redraw(save_data, vram);
+            }
+
+            return Callback::None;
+        }
+
+        // Skip straight to the end of the reveal
+        if pressed {
+            self.frame = SCORES_FRAME;
         } else {
-            Callback::None
+            self.frame += 1;
+        }
+
+        if self.frame <= SCORE_COUNT_LENGTH
+            || self.frame == MAX_COMBO_FRAME
+            || self.frame == ACCURACY_FRAME
+            || self.frame == SCORES_FRAME
+        {
+            self.redraw(save_data, vram);
         }
+
+        self.update_grade();
+
+        if self.frame == SCORES_FRAME && self.new_record() {
+            sfx::play(mixer, Sfx::NewRecord, save_data.get_settings().sfx_volume());
+        }
+
+        Callback::None
     }
 }
@@ -0,0 +1,134 @@
+use core::{fmt::Write, marker::PhantomData};
+
+use agb::{
+    display::{
+        object::{OamManaged, Object},
+        tiled::{
+            MapLoan, RegularBackgroundSize, RegularMap, TileFormat, Tiled0, TiledMap, VRamManager,
+        },
+        Priority,
+    },
+    include_background_gfx,
+    input::{Button, ButtonController},
+    sound::mixer::Mixer,
+};
+
+use crate::{
+    save_data::SaveDataManager,
+    sfx::{self, Sfx},
+    FONT,
+};
+
+use super::{Callback, SetState, State};
+
+include_background_gfx!(background, tiles => "assets/menu_tiles.aseprite");
+
+pub struct SaveWarningState<'a, 'b> {
+    next: Option<SetState>, // State to continue to once dismissed
+
+    bg: Option<MapLoan<'b, RegularMap>>,
+    text: Option<MapLoan<'b, RegularMap>>,
+
+    _data: PhantomData<Object<'a>>,
+}
+
+impl<'a, 'b> SaveWarningState<'a, 'b> {
+    pub fn new(next: SetState) -> Self {
+        Self {
+            next: Some(next),
+
+            bg: None,
+            text: None,
+
+            _data: PhantomData,
+        }
+    }
+}
+
+impl<'a, 'b> State<'a, 'b> for SaveWarningState<'a, 'b> {
+    fn init(
+        &mut self,
+        _save_data: &mut SaveDataManager,
+        _object_gfx: &'a OamManaged,
+        tiled0: &'b Tiled0<'b>,
+        vram: &mut VRamManager,
+        _mixer: &mut Mixer,
+    ) {
+        // Background
+        vram.set_background_palettes(background::PALETTES);
+
+        let mut bg = tiled0.background(
+            Priority::P3,
+            RegularBackgroundSize::Background32x32,
+            TileFormat::FourBpp,
+        );
+
+        let mut text = tiled0.background(
+            Priority::P1,
+            RegularBackgroundSize::Background32x32,
+            TileFormat::FourBpp,
+        );
+
+        for y in 0..20u16 {
+            for x in 0..32u16 {
+                let tile_id = if y == 0 {
+                    0
+                } else if y == 1 {
+                    1
+                } else {
+                    2
+                };
+
+                bg.set_tile(
+                    vram,
+                    (x, y).into(),
+                    &background::tiles.tiles,
+                    background::tiles.tile_settings[tile_id],
+                );
+            }
+        }
+
+        bg.commit(vram);
+        bg.show();
+
+        self.bg = Some(bg);
+
+        let mut renderer = FONT.render_text((3u16, 0u16).into());
+        let mut writer = renderer.writer(3, 0, &mut text, vram);
+
+        write!(
+            writer,
+            "Saving failed!\n\nScores will not be saved\nuntil saving works again\n\nPress A to continue",
+        )
+        .unwrap();
+
+        writer.commit();
+
+        text.commit(vram);
+        text.show();
+
+        self.text = Some(text);
+    }
+
+    fn update(
+        &mut self,
+        save_data: &mut SaveDataManager,
+        _object_gfx: &'a OamManaged,
+        _vram: &mut VRamManager,
+        mixer: &mut Mixer,
+        input: &ButtonController,
+    ) -> Callback {
+        if input.is_just_pressed(Button::A) || input.is_just_pressed(Button::START) {
+            if let Some(next) = self.next.take() {
+                sfx::play(
+                    mixer,
+                    Sfx::MenuSelect,
+                    save_data.get_settings().sfx_volume(),
+                );
+                return Callback::SetState(next);
+            }
+        }
+
+        Callback::None
+    }
+}
@@ -0,0 +1,211 @@
+use core::fmt::Write;
+
+use agb::{
+    display::{
+        font::TextRenderer,
+        object::{OamManaged, Object, TagMap},
+        tiled::{
+            MapLoan, RegularBackgroundSize, RegularMap, TileFormat, Tiled0, TiledMap, VRamManager,
+        },
+        Priority,
+    },
+    include_aseprite, include_background_gfx,
+    input::{Button, ButtonController},
+    sound::mixer::Mixer,
+};
+
+use crate::{
+    save_data::SaveDataManager,
+    sfx::{self, Sfx, MAX_VOLUME},
+    FONT,
+};
+
+use super::{Callback, State};
+
+include_background_gfx!(background, tiles => "assets/menu_tiles.aseprite");
+
+const GRAPHICS: &TagMap = include_aseprite!("assets/menu_selector.aseprite").tags();
+
+const OPTIONS: [&str; 2] = ["No fail", "SFX volume"];
+
+fn on_off(value: bool) -> &'static str {
+    if value {
+        "On"
+    } else {
+        "Off"
+    }
+}
+
+pub struct SettingsState<'a, 'b> {
+    bg: Option<MapLoan<'b, RegularMap>>,
+    text: Option<(MapLoan<'b, RegularMap>, TextRenderer<'b>)>,
+    selector_object: Object<'a>,
+    current_option: usize,
+}
+
+impl<'a, 'b> SettingsState<'a, 'b> {
+    pub fn new(object_gfx: &'a OamManaged) -> Self {
+        let sprite = GRAPHICS.get("selector").sprite(0);
+        let mut selector_object = object_gfx.object_sprite(sprite);
+        selector_object.show();
+        selector_object.set_position((4, 13).into());
+
+        Self {
+            bg: None,
+            text: None,
+            selector_object,
+            current_option: 0,
+        }
+    }
+
+    fn redraw(&mut self, save_data: &mut SaveDataManager, vram: &mut VRamManager) {
+        if let Some((text, renderer)) = &mut self.text {
+            text.clear(vram);
+            renderer.clear(vram);
+
+            let settings = save_data.get_settings();
+
+            let mut writer = renderer.writer(3, 0, text, vram);
+
+            writeln!(writer, "Settings:",).unwrap();
+            for (i, option) in OPTIONS.iter().enumerate() {
+                match i {
+                    0 => writeln!(writer, "{}: {}", option, on_off(settings.no_fail())),
+                    1 => writeln!(
+                        writer,
+                        "{}: < {}/{} >",
+                        option,
+                        settings.sfx_volume(),
+                        MAX_VOLUME
+                    ),
+                    _ => unreachable!(),
+                }
+                .unwrap();
+            }
+
+            writer.commit();
+
+            text.commit(vram);
+            text.show();
+        }
+    }
+}
+
+impl<'a, 'b> State<'a, 'b> for SettingsState<'a, 'b> {
+    fn init(
+        &mut self,
+        save_data: &mut SaveDataManager,
+        _object_gfx: &'a OamManaged,
+        tiled0: &'b Tiled0<'b>,
+        vram: &mut VRamManager,
+        _mixer: &mut Mixer,
+    ) {
+        // Background
+        vram.set_background_palettes(background::PALETTES);
+
+        let mut bg = tiled0.background(
+            Priority::P3,
+            RegularBackgroundSize::Background32x32,
+            TileFormat::FourBpp,
+        );
+
+        let text = tiled0.background(
+            Priority::P1,
+            RegularBackgroundSize::Background32x32,
+            TileFormat::FourBpp,
+        );
+
+        for y in 0..20u16 {
+            for x in 0..32u16 {
+                let tile_id = if y == 0 {
+                    0
+                } else if y == 1 {
+                    1
+                } else {
+                    2
+                };
+
+                bg.set_tile(
+                    vram,
+                    (x, y).into(),
+                    &background::tiles.tiles,
+                    background::tiles.tile_settings[tile_id],
+                );
+            }
+        }
+
+        bg.commit(vram);
+        bg.show();
+
+        self.bg = Some(bg);
+
+        let renderer = FONT.render_text((3u16, 0u16).into());
+
+        self.text = Some((text, renderer));
+
+        self.redraw(save_data, vram);
+    }
+
+    fn update(
+        &mut self,
+        save_data: &mut SaveDataManager,
+        _object_gfx: &'a OamManaged,
+        vram: &mut VRamManager,
+        mixer: &mut Mixer,
+        input: &ButtonController,
+    ) -> Callback {
+        let mut settings = save_data.get_settings();
+
+        if input.is_just_pressed(Button::UP) && self.current_option > 0 {
+            self.current_option -= 1;
+            sfx::play(mixer, Sfx::MenuMove, settings.sfx_volume());
+        }
+
+        if input.is_just_pressed(Button::DOWN) && self.current_option < OPTIONS.len() - 1 {
+            self.current_option += 1;
+            sfx::play(mixer, Sfx::MenuMove, settings.sfx_volume());
+        }
+
+        let y = ((self.current_option + 1) * 14) - 1;
+        self.selector_object.set_position((4, y as i32).into());
+
+        if let Some(bg) = &mut self.bg {
+            bg.commit(vram);
+        }
+
+        let mut changed = false;
+
+        if input.is_just_pressed(Button::A) && self.current_option == 0 {
+            settings.set_no_fail(!settings.no_fail());
+            changed = true;
+        }
+
+        // Volume is adjusted with left and right, playing a sound to preview it
+        if self.current_option == 1 {
+            let volume = settings.sfx_volume();
+
+            if input.is_just_pressed(Button::LEFT) && volume > 0 {
+                settings.set_sfx_volume(volume - 1);
+                changed = true;
+            }
+
+            if input.is_just_pressed(Button::RIGHT) && volume < MAX_VOLUME {
+                settings.set_sfx_volume(volume + 1);
+                changed = true;
+            }
+        }
+
+        if changed {
+            sfx::play(mixer, Sfx::MenuSelect, settings.sfx_volume());
+            save_data.set_settings(settings);
+            self.redraw(save_data, vram);
+        }
+
+        if input.is_just_pressed(Button::B) {
+            sfx::play(mixer, Sfx::MenuBack, settings.sfx_volume());
+            Callback::SetState(super::SetState::MainMenu)
+        } else {
+            Callback::None
+        }
+    }
+}
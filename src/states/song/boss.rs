@@ -0,0 +1,103 @@
+use agb::{
+    display::{
+        object::{OamManaged, Object},
+        Priority,
+    },
+    fixnum::Vector2D,
+};
+
+use super::GRAPHICS;
+
+// Where the boss stands once it has entered, and how fast it walks on and off
+const BOSS_X: i32 = agb::display::WIDTH - 40;
+const BOSS_Y: i32 = 100;
+const BOSS_SPEED: i32 = 2;
+
+// Frames the boss holds its arm up for while throwing a note
+const THROW_LENGTH: usize = 40;
+
+#[derive(Clone, Copy, PartialEq)]
+enum BossState {
+    Away,
+    Entering,
+    Present,
+    Exiting,
+}
+
+pub struct Boss<'a> {
+    object: Object<'a>,
+    state: BossState,
+    x: i32,
+    throw_frames: usize, // Remaining frames of the throw animation
+}
+
+impl<'a> Boss<'a> {
+    pub fn new(object_gfx: &'a OamManaged) -> Self {
+        let sprite = GRAPHICS.get("boss_idle").sprite(0);
+        let mut object = object_gfx.object_sprite(sprite);
+        object.set_priority(Priority::P2);
+        object.hide();
+
+        Self {
+            object,
+            state: BossState::Away,
+            x: agb::display::WIDTH,
+            throw_frames: 0,
+        }
+    }
+
+    pub fn enter(&mut self) {
+        if matches!(self.state, BossState::Away | BossState::Exiting) {
+            self.state = BossState::Entering;
+            self.object.show();
+        }
+    }
+
+    pub fn exit(&mut self) {
+        if matches!(self.state, BossState::Entering | BossState::Present) {
+            self.state = BossState::Exiting;
+        }
+    }
+
+    pub fn throw(&mut self) {
+        self.throw_frames = THROW_LENGTH;
+    }
+
+    /// Where thrown notes start from
+    pub fn hand(&self) -> Vector2D<i32> {
+        Vector2D::new(self.x - 8, BOSS_Y - 12)
+    }
+
+    pub fn update(&mut self, object_gfx: &'a OamManaged, frame: usize) {
+        match self.state {
+            BossState::Away => return,
+            BossState::Entering => {
+                self.x = (self.x - BOSS_SPEED).max(BOSS_X);
+
+                if self.x == BOSS_X {
+                    self.state = BossState::Present;
+                }
+            }
+            BossState::Present => (),
+            BossState::Exiting => {
+                self.x += BOSS_SPEED;
+
+                if self.x >= agb::display::WIDTH {
+                    self.state = BossState::Away;
+                    self.object.hide();
+                }
+            }
+        }
+
+        let sprite = if self.throw_frames > 0 {
+            self.throw_frames -= 1;
+            GRAPHICS.get("boss_throw").animation_sprite(frame / 8)
+        } else {
+            GRAPHICS.get("boss_idle").animation_sprite(frame / 16)
+        };
+
+        self.object
+            .set_sprite(object_gfx.sprite(sprite))
+            .set_position(Vector2D::new(self.x, BOSS_Y));
+    }
+}
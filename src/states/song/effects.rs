@@ -0,0 +1,170 @@
+use agb::{
+    display::{
+        object::{OamManaged, Object, Tag},
+        Priority,
+    },
+    fixnum::Vector2D,
+};
+use alloc::vec::Vec;
+
+use crate::{score::Judgement, song_data::Track};
+
+use super::{song::PERFECT_LOCATION, GRAPHICS};
+
+// Most effects shown at once, so they can never use up the objects notes need.
+// The oldest effect is dropped to make room for a new one
+const MAX_PARTICLES: usize = 8;
+
+// Frames each frame of a burst shows for
+const BURST_SPEED: usize = 3;
+
+// Judgement text rises this far over its lifetime
+const TEXT_LENGTH: usize = 24;
+const TEXT_RISE: i32 = 8;
+
+// Screen shake on misses, in frames and pixels
+const SHAKE_LENGTH: usize = 12;
+const SHAKE_AMOUNT: i32 = 3;
+
+enum ParticleKind {
+    Burst(&'static Tag), // Plays through once
+    Text,                // Rises then disappears
+}
+
+struct Particle<'a> {
+    object: Object<'a>,
+    kind: ParticleKind,
+    position: Vector2D<i32>,
+    frames: usize, // Frames since appearing
+}
+
+impl<'a> Particle<'a> {
+    fn new(
+        object_gfx: &'a OamManaged,
+        tag: &'static Tag,
+        kind: ParticleKind,
+        position: Vector2D<i32>,
+    ) -> Self {
+        let mut object = object_gfx.object_sprite(tag.sprite(0));
+        object.set_priority(Priority::P1).set_position(position);
+        object.show();
+
+        Self {
+            object,
+            kind,
+            position,
+            frames: 0,
+        }
+    }
+
+    /// Advances the particle, returning whether it has finished
+    fn update(&mut self, object_gfx: &'a OamManaged) -> bool {
+        self.frames += 1;
+
+        match self.kind {
+            ParticleKind::Burst(tag) => {
+                let index = self.frames / BURST_SPEED;
+
+                if index >= tag.sprites().len() {
+                    return true;
+                }
+
+                if self.frames % BURST_SPEED == 0 {
+                    self.object.set_sprite(object_gfx.sprite(tag.sprite(index)));
+                }
+            }
+            ParticleKind::Text => {
+                if self.frames >= TEXT_LENGTH {
+                    return true;
+                }
+
+                let rise = TEXT_RISE * self.frames as i32 / TEXT_LENGTH as i32;
+                self.object
+                    .set_position(self.position - Vector2D::new(0, rise));
+            }
+        }
+
+        false
+    }
+}
+
+pub struct Effects<'a> {
+    particles: Vec<Particle<'a>>,
+    shake_frames: usize, // Remaining frames of screen shake
+}
+
+impl<'a> Effects<'a> {
+    pub fn new() -> Self {
+        Self {
+            particles: Vec::with_capacity(MAX_PARTICLES),
+            shake_frames: 0,
+        }
+    }
+
+    fn spawn(&mut self, particle: Particle<'a>) {
+        if self.particles.len() >= MAX_PARTICLES {
+            self.particles.remove(0);
+        }
+
+        self.particles.push(particle);
+    }
+
+    /// Shows a burst and judgement text where a note on `track` was hit
+    pub fn hit(&mut self, object_gfx: &'a OamManaged, judgement: Judgement, track: Track) {
+        let (burst, text) = match judgement {
+            Judgement::Perfect => ("burst_perfect", "text_perfect"),
+            Judgement::Great => ("burst_great", "text_great"),
+        };
+
+        self.judge(object_gfx, burst, text, track);
+    }
+
+    /// Shows a burst and judgement text for a miss, and shakes the screen
+    pub fn miss(&mut self, object_gfx: &'a OamManaged, track: Track) {
+        self.judge(object_gfx, "burst_miss", "text_miss", track);
+        self.shake();
+    }
+
+    pub fn shake(&mut self) {
+        self.shake_frames = SHAKE_LENGTH;
+    }
+
+    fn judge(&mut self, object_gfx: &'a OamManaged, burst: &str, text: &str, track: Track) {
+        let burst_tag = GRAPHICS.get(burst);
+        let position = Vector2D::new(PERFECT_LOCATION, track.y_pos());
+
+        self.spawn(Particle::new(
+            object_gfx,
+            burst_tag,
+            ParticleKind::Burst(burst_tag),
+            position,
+        ));
+
+        // Text is 32x8, centred over the burst
+        self.spawn(Particle::new(
+            object_gfx,
+            GRAPHICS.get(text),
+            ParticleKind::Text,
+            position - Vector2D::new(8, 8),
+        ));
+    }
+
+    pub fn update(&mut self, object_gfx: &'a OamManaged) {
+        self.particles
+            .retain_mut(|particle| !particle.update(object_gfx));
+
+        self.shake_frames = self.shake_frames.saturating_sub(1);
+    }
+
+    /// Background offset for screen shake, alternating sides as it dies down
+    pub fn shake_offset(&self) -> Vector2D<i16> {
+        let amount = SHAKE_AMOUNT * self.shake_frames as i32 / SHAKE_LENGTH as i32;
+        let x = if (self.shake_frames / 2) % 2 == 0 {
+            amount
+        } else {
+            -amount
+        };
+
+        Vector2D::new(x as i16, 0)
+    }
+}
@@ -0,0 +1,101 @@
+use agb::display::{
+    font::TextRenderer,
+    tiled::{MapLoan, RegularMap, TiledMap, VRamManager},
+};
+use core::fmt::Write;
+
+use crate::{BIG_FONT, FONT};
+
+use super::song::{Song, FEVER_MAX};
+
+const HP_SEGMENTS: usize = 20;
+const FEVER_SEGMENTS: usize = 20;
+
+pub struct Hud<'b> {
+    map: MapLoan<'b, RegularMap>,
+    score: TextRenderer<'b>,
+    combo: TextRenderer<'b>,
+    hp: TextRenderer<'b>,
+    fever: TextRenderer<'b>,
+    banner: TextRenderer<'b>,
+}
+
+impl<'b> Hud<'b> {
+    pub fn new(map: MapLoan<'b, RegularMap>) -> Self {
+        Self {
+            map,
+            score: BIG_FONT.render_text((0u16, 0u16).into()),
+            combo: BIG_FONT.render_text((13u16, 3u16).into()),
+            hp: BIG_FONT.render_text((20u16, 0u16).into()),
+            fever: BIG_FONT.render_text((20u16, 2u16).into()),
+            banner: FONT.render_text((9u16, 6u16).into()),
+        }
+    }
+
+    pub fn map(&mut self) -> &mut MapLoan<'b, RegularMap> {
+        &mut self.map
+    }
+
+    pub fn redraw(&mut self, vram: &mut VRamManager, song: &Song) {
+        self.map.clear(vram);
+
+        self.score.clear(vram);
+        let mut writer = self.score.writer(3, 0, &mut self.map, vram);
+
+        write!(writer, " {}\n SCORE", song.score()).unwrap();
+
+        writer.commit();
+
+        self.combo.clear(vram);
+
+        if song.combo() >= 5 {
+            let mut writer = self.combo.writer(3, 0, &mut self.map, vram);
+            write!(writer, "{:^9}\nCOMBO", song.combo()).unwrap();
+            writer.commit();
+        }
+
+        self.hp.clear(vram);
+
+        // Round up so any HP left still shows
+        let filled = (song.hp() * HP_SEGMENTS).div_ceil(song.max_hp());
+
+        let mut writer = self.hp.writer(3, 0, &mut self.map, vram);
+        write!(writer, "HP ").unwrap();
+        for segment in 0..HP_SEGMENTS {
+            write!(writer, "{}", if segment < filled { '|' } else { '.' }).unwrap();
+        }
+        writer.commit();
+
+        self.fever.clear(vram);
+
+        let mut writer = self.fever.writer(3, 0, &mut self.map, vram);
+        if song.fever_active() {
+            write!(writer, "FEVER!").unwrap();
+        } else if song.fever_ready() {
+            write!(writer, "SELECT: FEVER").unwrap();
+        } else {
+            let filled = song.fever_gauge() * FEVER_SEGMENTS / FEVER_MAX;
+
+            write!(writer, "FV ").unwrap();
+            for segment in 0..FEVER_SEGMENTS {
+                write!(writer, "{}", if segment < filled { '|' } else { '.' }).unwrap();
+            }
+        }
+        writer.commit();
+    }
+
+    /// Adds the next letter of the end of song banner
+    pub fn write_banner(&mut self, vram: &mut VRamManager, letter: char) {
+        let mut writer = self.banner.writer(3, 0, &mut self.map, vram);
+        write!(writer, "{}", letter).unwrap();
+        writer.commit();
+    }
+
+    pub fn commit(&mut self, vram: &mut VRamManager) {
+        self.map.commit(vram);
+    }
+
+    pub fn show(&mut self) {
+        self.map.show();
+    }
+}
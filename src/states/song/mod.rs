@@ -1,181 +1,322 @@
 use agb::{
     display::{
-        font::TextRenderer,
-        object::{OamManaged, TagMap},
-        tiled::{
-            MapLoan, RegularBackgroundSize, RegularMap, TileFormat, Tiled1, TiledMap, VRamManager,
-        },
+        object::{OamManaged, Object, TagMap},
+        palette16::Palette16,
+        tile_data::TileData,
+        tiled::{RegularBackgroundSize, TileFormat, Tiled0, VRamManager},
         Priority,
     },
     include_aseprite, include_background_gfx,
     input::{Button, ButtonController},
     sound::mixer::{ChannelId, Mixer, SoundChannel},
 };
-use core::fmt::Write;
 
-use crate::{save_data::SaveDataManager, songs::SongID, BIG_FONT};
+use alloc::vec::Vec;
+
+use crate::{
+    character::Animation,
+    save_data::SaveDataManager,
+    sfx::{self, Sfx},
+    song_data::{Background, Theme, Track},
+    songs::SongID,
+};
 
 use self::{
+    hud::Hud,
+    parallax::Parallax,
     pause::{Pause, PauseItem},
-    player::{Animation, Player},
+    player::Player,
     song::{Song, SongResult},
 };
 
 use super::{Callback, SetState, State};
 
+mod boss;
+mod effects;
+mod hud;
 mod note;
+mod parallax;
 mod pause;
 mod player;
 mod song;
 
-include_background_gfx!(background, tiles => "assets/background.aseprite");
+include_background_gfx!(
+    background,
+    tiles => "assets/background.aseprite",
+    standard => deduplicate "assets/themes/standard.aseprite",
+    night => deduplicate "assets/themes/night.aseprite",
+    sunset => deduplicate "assets/themes/sunset.aseprite"
+);
+
+impl Theme {
+    fn layers(&self) -> &'static TileData {
+        match self {
+            Theme::Standard => &background::standard,
+            Theme::Night => &background::night,
+            Theme::Sunset => &background::sunset,
+        }
+    }
+}
 
-const GRAPHICS: &TagMap = include_aseprite!(
+// Also used by the character select preview, so the player sheets are only embedded once
+pub const GRAPHICS: &TagMap = include_aseprite!(
     "assets/new_player.aseprite",
+    "assets/player_fever.aseprite",
+    "assets/player_knight.aseprite",
+    "assets/player_rogue.aseprite",
+    "assets/player_paladin.aseprite",
+    "assets/player_ranger.aseprite",
     "assets/note.aseprite",
-    "assets/pause_select.aseprite"
+    "assets/ghost_note.aseprite",
+    "assets/obstacle.aseprite",
+    "assets/pickups.aseprite",
+    "assets/boss.aseprite",
+    "assets/effects.aseprite",
+    "assets/judgement_text.aseprite",
+    "assets/pause_select.aseprite",
+    "assets/judgement.aseprite"
 )
 .tags();
 
-const FLOOR_HEIGHT: u16 = 16;
+pub const FLOOR_HEIGHT: u16 = 16;
 const JUDGEMENT_AREA: u16 = 5;
 const JUDGEMENT_HIGH: u16 = 10;
 const JUDGEMENT_LOW: u16 = 13;
 
+// Full combo banner, revealed a letter at a time then held before the results
+const BANNER_LETTER_LENGTH: usize = 4;
+const BANNER_HOLD_LENGTH: usize = 90;
+
+// Text is always drawn with the first palette, so the HUD keeps its colours in fever
+const TEXT_PALETTE: usize = 0;
+
+/// Background palettes used during fever, with the colour channels rotated
+/// so blues turn red. The text palette is left as it is
+fn fever_palettes(palettes: &[Palette16]) -> Vec<Palette16> {
+    palettes
+        .iter()
+        .enumerate()
+        .map(|(index, palette)| {
+            let mut palette = palette.clone();
+
+            if index == TEXT_PALETTE {
+                return palette;
+            }
+
+            for i in 0..16 {
+                // Colours are stored as 0bBBBBBGGGGGRRRRR
+                let colour = palette.colour(i);
+                let (r, g, b) = (colour & 0x1F, (colour >> 5) & 0x1F, (colour >> 10) & 0x1F);
+
+                palette.update_colour(i, g << 10 | r << 5 | b);
+            }
+
+            palette
+        })
+        .collect()
+}
+
 pub struct SongState<'a, 'b> {
-    map: Option<MapLoan<'b, RegularMap>>,
-    text: Option<(MapLoan<'b, RegularMap>, TextRenderer<'b>, TextRenderer<'b>)>,
+    background: Background,
+    parallax: Option<Parallax<'b>>,
+    hud: Option<Hud<'b>>,
     song_id: SongID,
     song: Song<'a>,
     player: Player<'a>,
     pause: Pause<'a>,
+    _judgement_markers: [Object<'a>; 2],
     music_channel: Option<ChannelId>,
     frame: usize,
     redraw_text: bool,
+    banner_frame: Option<usize>, // Some while showing the full combo banner
+    fever: bool,
+    fever_palettes: Vec<Palette16>,
 }
 
 impl<'a, 'b> SongState<'a, 'b> {
     pub fn new(song_id: SongID, object_gfx: &'a OamManaged) -> Self {
+        // Drawn behind notes, where they should be hit
+        let judgement_markers = [Track::High, Track::Low].map(|track| {
+            let mut object = object_gfx.object_sprite(GRAPHICS.get("judgement").sprite(0));
+            object
+                .set_priority(Priority::P3)
+                .set_position((JUDGEMENT_AREA as i32 * 8, track.y_pos()).into());
+            object.show();
+            object
+        });
+
+        // Songs with their own background bring their own palettes
+        let background = song_id.background().copied().unwrap_or(Background::new(
+            song_id.theme().layers(),
+            &background::tiles,
+            background::PALETTES,
+        ));
+
         Self {
-            map: None,
-            text: None,
+            background,
+            parallax: None,
+            hud: None,
             song_id,
-            song: Song::new(song_id),
+            song: Song::new(song_id, object_gfx),
             player: Player::new(object_gfx),
             pause: Pause::new(object_gfx),
+            _judgement_markers: judgement_markers,
             music_channel: None,
             frame: 0,
             redraw_text: true,
+            banner_frame: None,
+            fever: false,
+            fever_palettes: fever_palettes(background.palettes()),
+        }
+    }
+
+    fn banner(&self) -> &'static str {
+        if self.song.all_perfect() {
+            "ALL PERFECT"
+        } else {
+            "FULL COMBO"
+        }
+    }
+
+    fn start_music(&mut self, mixer: &mut Mixer) {
+        // High priority so sound effects can't take its channel
+        let mut channel = SoundChannel::new_high_priority(self.song_id.sound());
+        channel.stereo();
+        self.music_channel = mixer.play_sound(channel);
+    }
+
+    fn finish(&mut self, save_data: &mut SaveDataManager, mixer: &mut Mixer) -> Callback {
+        if let Some(channel) = self.music_channel.as_ref().and_then(|id| mixer.channel(id)) {
+            channel.stop();
+        }
+
+        save_data.add_play_time(self.frame);
+
+        Callback::SetState(SetState::ResultScreen(
+            self.song_id,
+            self.song.final_score(),
+        ))
+    }
+
+    /// Swaps the background palette and player animation when fever starts or ends
+    fn update_fever(&mut self, vram: &mut VRamManager) {
+        if self.song.fever_active() == self.fever {
+            return;
+        }
+
+        self.fever = self.song.fever_active();
+
+        if self.fever {
+            vram.set_background_palettes(&self.fever_palettes);
+        } else {
+            vram.set_background_palettes(self.background.palettes());
         }
+
+        self.player.set_fever(self.fever);
+    }
+
+    fn update_banner(
+        &mut self,
+        save_data: &mut SaveDataManager,
+        object_gfx: &'a OamManaged,
+        vram: &mut VRamManager,
+        mixer: &mut Mixer,
+    ) -> Callback {
+        let banner_frame = self.banner_frame.unwrap_or(0);
+        let banner = self.banner();
+
+        if banner_frame >= banner.len() * BANNER_LETTER_LENGTH + BANNER_HOLD_LENGTH {
+            return self.finish(save_data, mixer);
+        }
+
+        if banner_frame % BANNER_LETTER_LENGTH == 0 {
+            if let Some(letter) = banner.chars().nth(banner_frame / BANNER_LETTER_LENGTH) {
+                if let Some(hud) = &mut self.hud {
+                    hud.write_banner(vram, letter);
+                    hud.commit(vram);
+                }
+            }
+        }
+
+        // Keep the player running while the banner shows
+        if banner_frame % 5 == 0 {
+            self.player.update();
+        }
+
+        self.player.draw(object_gfx);
+
+        self.banner_frame = Some(banner_frame + 1);
+
+        Callback::None
     }
 }
 
 impl<'a, 'b> State<'a, 'b> for SongState<'a, 'b> {
     fn init(
         &mut self,
-        _save_data: &mut SaveDataManager,
+        save_data: &mut SaveDataManager,
         _object_gfx: &'a OamManaged,
-        tiled1: &'b Tiled1<'b>,
+        tiled0: &'b Tiled0<'b>,
         vram: &mut VRamManager,
         mixer: &mut Mixer,
     ) {
         // Background
-        vram.set_background_palettes(background::PALETTES);
+        vram.set_background_palettes(self.background.palettes());
 
-        let mut map = tiled1.regular(
-            Priority::P3,
-            RegularBackgroundSize::Background32x32,
-            TileFormat::FourBpp,
-        );
+        let mut parallax = Parallax::new(tiled0, vram, self.background.layers());
+        parallax.update(vram, 0, (0i16, 0i16).into());
+        parallax.show();
+
+        self.parallax = Some(parallax);
 
-        let mut text = tiled1.regular(
+        let text = tiled0.background(
             Priority::P1,
             RegularBackgroundSize::Background32x32,
             TileFormat::FourBpp,
         );
 
-        for y in 0..20u16 {
-            for x in 0..32u16 {
-                let tile = if y < FLOOR_HEIGHT - 1 {
-                    if x == JUDGEMENT_AREA {
-                        if y == JUDGEMENT_HIGH {
-                            4
-                        } else if y == JUDGEMENT_HIGH + 1 {
-                            6
-                        } else if y == JUDGEMENT_LOW {
-                            4
-                        } else if y == JUDGEMENT_LOW + 1 {
-                            6
-                        } else {
-                            0
-                        }
-                    } else if x == JUDGEMENT_AREA + 1 {
-                        if y == JUDGEMENT_HIGH {
-                            5
-                        } else if y == JUDGEMENT_HIGH + 1 {
-                            7
-                        } else if y == JUDGEMENT_LOW {
-                            5
-                        } else if y == JUDGEMENT_LOW + 1 {
-                            7
-                        } else {
-                            0
-                        }
-                    } else {
-                        0
-                    }
-                } else if y < FLOOR_HEIGHT {
-                    1
-                } else if y < FLOOR_HEIGHT + 1 {
-                    2
-                } else {
-                    3
-                };
-
-                map.set_tile(
-                    vram,
-                    (x, y).into(),
-                    &background::tiles.tiles,
-                    background::tiles.tile_settings[tile],
-                );
-            }
-        }
+        let mut hud = Hud::new(text);
 
-        map.commit(vram);
-        map.show();
+        hud.commit(vram);
+        hud.show();
 
-        self.map = Some(map);
+        self.hud = Some(hud);
 
-        let score_renderer = BIG_FONT.render_text((0u16, 0u16).into());
-        let combo_renderer = BIG_FONT.render_text((13u16, 3u16).into());
+        let character = save_data.get_profile().character();
+        self.player.set_character(character);
+        self.song.set_character(character);
 
-        text.commit(vram);
-        text.show();
+        let settings = save_data.get_settings();
+        self.song.set_no_fail(settings.no_fail());
+        self.song.set_sfx_volume(settings.sfx_volume());
 
-        self.text = Some((text, score_renderer, combo_renderer));
-
-        // Music
-        mixer.enable();
-
-        let mut channel = SoundChannel::new(self.song_id.sound());
-        channel.stereo();
-        self.music_channel = mixer.play_sound(channel);
+        if self.song_id.lead_in() == 0 {
+            self.start_music(mixer);
+        }
     }
 
     fn update(
         &mut self,
-        _save_data: &mut SaveDataManager,
+        save_data: &mut SaveDataManager,
         object_gfx: &'a OamManaged,
         vram: &mut VRamManager,
         mixer: &mut Mixer,
         input: &ButtonController,
     ) -> Callback {
+        if self.banner_frame.is_some() {
+            return self.update_banner(save_data, object_gfx, vram, mixer);
+        }
+
         if !self.pause.paused() {
             self.frame += 1;
 
+            // Waits for notes that spawn before the music starts
+            if self.frame == self.song_id.lead_in() {
+                self.start_music(mixer);
+            }
+
             if input.is_just_pressed(Button::START) {
-                self.pause
-                    .pause(mixer, self.music_channel.as_ref().unwrap());
+                self.pause.pause(mixer, self.music_channel.as_ref());
             }
 
             if input.is_just_pressed(Button::R) {
@@ -186,97 +327,101 @@ impl<'a, 'b> State<'a, 'b> for SongState<'a, 'b> {
                 self.player.set_animation(Animation::AttackHigh);
             }
 
+            if input.is_just_pressed(Button::SELECT) && self.song.activate_fever() {
+                self.redraw_text = true;
+            }
+
             // Every 5th frame update the player sprite
             if self.frame % 5 == 0 {
                 self.player.update();
             }
 
-            match self.song.update(object_gfx, input, self.frame) {
+            match self
+                .song
+                .update(object_gfx, mixer, input, self.frame, self.player.track())
+            {
                 SongResult::UpdateText => self.redraw_text = true,
                 SongResult::Finished => {
-                    if let Some(channel) = mixer.channel(self.music_channel.as_ref().unwrap()) {
-                        channel.stop();
+                    if self.song.full_combo() {
+                        self.banner_frame = Some(0);
+                    } else {
+                        return self.finish(save_data, mixer);
                     }
-
-                    return Callback::SetState(SetState::ResultScreen(
-                        self.song_id,
-                        self.song.final_score(),
-                    ));
                 }
+                SongResult::Failed => return self.finish(save_data, mixer),
                 SongResult::None => (),
             }
 
+            self.update_fever(vram);
+
             self.player.draw(object_gfx);
 
-            if let Some(map) = &mut self.map {
-                map.commit(vram);
+            if let Some(parallax) = &mut self.parallax {
+                parallax.update(vram, self.frame, self.song.shake_offset());
             }
 
-            if let Some((text, score_renderer, combo_renderer)) = &mut self.text {
+            if let Some(hud) = &mut self.hud {
                 if self.redraw_text {
-                    text.clear(vram);
-
-                    score_renderer.clear(vram);
-                    let mut writer = score_renderer.writer(3, 0, text, vram);
-
-                    write!(writer, " {}\n SCORE", self.song.score()).unwrap();
-
-                    writer.commit();
-
-                    combo_renderer.clear(vram);
-
-                    if self.song.combo() >= 5 {
-                        let mut writer = combo_renderer.writer(3, 0, text, vram);
-                        write!(writer, "{:^9}\nCOMBO", self.song.combo()).unwrap();
-                        writer.commit();
-                    }
-
+                    hud.redraw(vram, &self.song);
                     self.redraw_text = false;
                 }
-                text.commit(vram);
+                hud.commit(vram);
             }
         } else {
+            let volume = save_data.get_settings().sfx_volume();
+
             if input.is_just_pressed(Button::LEFT) {
                 self.pause.previous_item();
+                sfx::play(mixer, Sfx::MenuMove, volume);
             }
 
             if input.is_just_pressed(Button::RIGHT) {
                 self.pause.next_item();
+                sfx::play(mixer, Sfx::MenuMove, volume);
             }
 
             if input.is_just_pressed(Button::A) {
+                sfx::play(mixer, Sfx::MenuSelect, volume);
+
                 match self.pause.item() {
                     PauseItem::Exit => {
-                        if let Some(channel) = mixer.channel(self.music_channel.as_ref().unwrap()) {
+                        if let Some(channel) =
+                            self.music_channel.as_ref().and_then(|id| mixer.channel(id))
+                        {
                             channel.stop();
                         }
 
+                        save_data.add_play_time(self.frame);
+
                         return Callback::SetState(SetState::SongMenu);
                     }
                     PauseItem::Restart => {
-                        if let Some(channel) = mixer.channel(self.music_channel.as_ref().unwrap()) {
+                        if let Some(channel) =
+                            self.music_channel.as_ref().and_then(|id| mixer.channel(id))
+                        {
                             channel.stop();
                         }
 
+                        save_data.add_play_time(self.frame);
+
                         return Callback::SetState(SetState::Song(self.song_id));
                     }
                     PauseItem::Resume => {
-                        self.pause
-                            .resume(mixer, self.music_channel.as_ref().unwrap());
+                        self.pause.resume(mixer, self.music_channel.as_ref());
                         self.redraw_text = true;
                     }
                 }
             }
 
             if input.is_just_pressed(Button::START) | input.is_just_pressed(Button::B) {
-                self.pause
-                    .resume(mixer, self.music_channel.as_ref().unwrap());
+                self.pause.resume(mixer, self.music_channel.as_ref());
                 self.redraw_text = true;
             }
 
-            if let Some((text, _, _)) = &mut self.text {
-                self.pause.render(text, vram, object_gfx);
-                text.commit(vram);
+            if let Some(hud) = &mut self.hud {
+                self.pause
+                    .render(hud.map(), vram, object_gfx, self.background.tiles());
+                hud.commit(vram);
             }
         }
 
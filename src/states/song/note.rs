@@ -1,17 +1,45 @@
 use agb::{
     display::{
-        object::{OamManaged, Object},
+        object::{OamManaged, Object, Sprite},
         Priority,
     },
-    fixnum::Vector2D,
+    fixnum::{Num, Vector2D},
 };
 
-use crate::song_data::Track;
+use crate::song_data::{Motion, MotionType, Skin, Track};
 
-use super::{GRAPHICS, JUDGEMENT_HIGH, JUDGEMENT_LOW};
+use super::{song::PERFECT_LOCATION, GRAPHICS, JUDGEMENT_HIGH, JUDGEMENT_LOW};
+
+// Distance a note covers from appearing to reaching the judgement area
+const TRAVEL_DISTANCE: i32 = agb::display::WIDTH - PERFECT_LOCATION;
+
+// Wave notes bob this high, shrinking to nothing as they reach their track
+const WAVE_HEIGHT: i32 = 12;
+const WAVE_PERIOD: i32 = 40;
+
+// Notes are positioned as 16x16, larger sprites are centred on that
+const NOTE_SIZE: i32 = 16;
+
+// Frames each frame of the hit animation shows for
+const HIT_ANIMATION_SPEED: usize = 4;
+
+// Ghost notes fade out between these locations, and are hidden past the end
+const GHOST_FADE_START: i32 = agb::display::WIDTH * 2 / 3;
+const GHOST_FADE_END: i32 = agb::display::WIDTH / 3;
+const GHOST_FADE_STEPS: i32 = 4;
+
+// Thrown notes land on their track here, and how high their arc peaks
+const THROW_LAND: i32 = agb::display::WIDTH / 2;
+const THROW_HEIGHT: i32 = 40;
+
+#[derive(Clone, Copy)]
+enum Path {
+    Straight,
+    Thrown { from: Vector2D<i32>, released: bool }, // Arcs from the boss's hand
+}
 
 impl Track {
-    fn y_pos(&self) -> i32 {
+    pub fn y_pos(&self) -> i32 {
         match self {
             Track::Low => 8 * JUDGEMENT_LOW as i32,
             Track::High => 8 * JUDGEMENT_HIGH as i32,
@@ -19,36 +47,227 @@ impl Track {
     }
 }
 
+#[derive(Clone, Copy, PartialEq)]
+pub enum NoteKind {
+    Note,
+    Obstacle,  // Must be dodged instead of hit
+    Heart,     // Collected by being on its track
+    MusicNote, // Collected by being on its track
+}
+
+impl NoteKind {
+    fn tag(&self) -> &'static str {
+        match self {
+            NoteKind::Note => "note",
+            NoteKind::Obstacle => "obstacle",
+            NoteKind::Heart => "heart",
+            NoteKind::MusicNote => "music_note",
+        }
+    }
+}
+
 pub struct Note<'a> {
     object: Object<'a>,
+    kind: NoteKind,
     track: Track,
     location: i32,
     hit: bool,
+    missed: bool,
+    passed: bool,       // Obstacle or pickup has passed the player
+    ghost: Option<i32>, // Current fade step of a ghost note
+    path: Path,
+    motion: Motion,
+    elapsed: i32, // Frames since appearing
+    skin: Skin,
+    offset: Vector2D<i32>, // Centres the current sprite
+    hit_frames: usize,     // Frames since being hit
+    sample: Option<&'static [u8]>,
 }
 
 impl<'a> Note<'a> {
-    pub fn new(object_gfx: &'a OamManaged, track: Track) -> Self {
-        let sprite = GRAPHICS.get("note").sprite(0);
+    pub fn new(object_gfx: &'a OamManaged, kind: NoteKind, track: Track, motion: Motion) -> Self {
+        let sprite = GRAPHICS.get(kind.tag()).sprite(0);
+        let offset = centre_offset(sprite);
         let mut object = object_gfx.object_sprite(sprite);
         object.set_priority(Priority::P2);
-        object.set_position(Vector2D::new(agb::display::WIDTH, track.y_pos()));
+        object.set_position(Vector2D::new(agb::display::WIDTH, track.y_pos()) + offset);
         object.show();
 
         Self {
             object,
+            kind,
             track,
             location: agb::display::WIDTH,
             hit: false,
+            missed: false,
+            passed: false,
+            ghost: None,
+            path: Path::Straight,
+            motion,
+            elapsed: 0,
+            skin: Skin::new(kind.tag(), "note_hit"),
+            offset,
+            hit_frames: 0,
+            sample: None,
+        }
+    }
+
+    fn set_sprite(&mut self, object_gfx: &'a OamManaged, sprite: &'static Sprite) {
+        self.offset = centre_offset(sprite);
+        self.object.set_sprite(object_gfx.sprite(sprite));
+    }
+
+    /// Draws the note with the given skin's sprites
+    pub fn skin(mut self, object_gfx: &'a OamManaged, skin: Skin) -> Self {
+        self.skin = skin;
+        self.set_sprite(object_gfx, GRAPHICS.get(skin.tag()).sprite(0));
+
+        self
+    }
+
+    /// Makes this a note thrown from `from`, hidden until it leaves the hand
+    pub fn thrown(mut self, from: Vector2D<i32>) -> Self {
+        self.object.hide();
+        self.path = Path::Thrown {
+            from,
+            released: false,
+        };
+
+        self
+    }
+
+    /// Progress towards the judgement area, from 0 when appearing to 1 on arrival
+    fn progress(&self) -> Num<i32, 8> {
+        let travel = self.motion.travel().max(1) as i32;
+
+        (Num::new(self.elapsed) / travel).min(1.into())
+    }
+
+    fn y_pos(&self) -> i32 {
+        let y = match self.path {
+            Path::Straight => self.track.y_pos(),
+            Path::Thrown { from, .. } => {
+                // Progress along the arc, from leaving the hand to landing
+                let t: Num<i32, 8> = (Num::new(from.x - self.location)
+                    / (from.x - THROW_LAND).max(1))
+                .clamp(0.into(), 1.into());
+
+                let to = self.track.y_pos();
+                let y = Num::new(from.y) + t * (to - from.y)
+                    - t * (Num::new(1) - t) * (THROW_HEIGHT * 4);
+
+                y.floor()
+            }
+        };
+
+        if self.motion.kind() == MotionType::Wave {
+            let angle: Num<i32, 8> = Num::new(self.elapsed) / WAVE_PERIOD;
+            let height = (Num::new(1) - self.progress()) * WAVE_HEIGHT;
+
+            y + (angle.sin() * height).floor()
+        } else {
+            y
         }
     }
 
-    pub fn draw(&mut self) {
+    /// Plays `sample` instead of the hit sound when hit
+    pub fn keysound(mut self, sample: Option<&'static [u8]>) -> Self {
+        self.sample = sample;
+
+        self
+    }
+
+    /// Makes this a ghost note, which fades out before it can be hit
+    pub fn ghost(mut self, object_gfx: &'a OamManaged) -> Self {
+        self.set_sprite(object_gfx, GRAPHICS.get("note_ghost").sprite(0));
+        self.ghost = Some(0);
+
+        self
+    }
+
+    pub fn draw(&mut self, object_gfx: &'a OamManaged) {
+        if self.hit {
+            // Play the hit animation through once, holding its last frame
+            let tag = GRAPHICS.get(self.skin.hit_tag());
+
+            if self.hit_frames % HIT_ANIMATION_SPEED == 0 {
+                let index = (self.hit_frames / HIT_ANIMATION_SPEED).min(tag.sprites().len() - 1);
+                self.set_sprite(object_gfx, tag.sprite(index));
+            }
+
+            self.hit_frames += 1;
+        }
+
         self.object
-            .set_position(Vector2D::new(self.location, self.track.y_pos()));
+            .set_position(Vector2D::new(self.location, self.y_pos()) + self.offset);
+
+        if let Path::Thrown { from, released } = &mut self.path {
+            if !*released && self.location <= from.x {
+                *released = true;
+                self.object.show();
+            }
+        }
+
+        if let Some(step) = self.ghost {
+            if self.hit {
+                return;
+            }
+
+            let new_step = ((GHOST_FADE_START - self.location) * GHOST_FADE_STEPS
+                / (GHOST_FADE_START - GHOST_FADE_END))
+                .clamp(0, GHOST_FADE_STEPS);
+
+            if new_step != step {
+                self.ghost = Some(new_step);
+
+                if new_step == GHOST_FADE_STEPS {
+                    self.object.hide();
+                } else {
+                    let sprite = GRAPHICS.get("note_ghost").sprite(new_step as usize);
+                    self.set_sprite(object_gfx, sprite);
+                }
+            }
+        }
+    }
+
+    pub fn update(&mut self) {
+        self.elapsed += 1;
+
+        let travel = self.motion.travel().max(1) as i32;
+
+        self.location = if self.elapsed >= travel {
+            // Past the judgement area every note carries on at its average speed
+            PERFECT_LOCATION - (self.elapsed - travel) * TRAVEL_DISTANCE / travel
+        } else {
+            let p = self.progress();
+
+            // Fraction of the distance left, which reaches 0 exactly on arrival
+            let remaining = match self.motion.kind() {
+                MotionType::Constant | MotionType::Wave => Num::new(1) - p,
+                MotionType::Accelerate => Num::new(1) - (p + p * p) / 2,
+                MotionType::Decelerate => Num::new(1) - (p * 3 - p * p) / 2,
+            };
+
+            PERFECT_LOCATION + (remaining * TRAVEL_DISTANCE).floor()
+        };
+    }
+
+    pub fn animate(&mut self, object_gfx: &'a OamManaged, frame: usize) {
+        // Hit and ghost notes have their own animations
+        if self.hit || self.ghost.is_some() {
+            return;
+        }
+
+        let sprite = GRAPHICS.get(self.skin.tag()).animation_sprite(frame);
+        self.set_sprite(object_gfx, sprite);
+    }
+
+    pub fn sample(&self) -> Option<&'static [u8]> {
+        self.sample
     }
 
-    pub fn update(&mut self, speed: i32) {
-        self.location -= speed;
+    pub fn kind(&self) -> NoteKind {
+        self.kind
     }
 
     pub fn location(&self) -> i32 {
@@ -63,9 +282,39 @@ impl<'a> Note<'a> {
         self.hit
     }
 
-    pub fn set_hit(&mut self, object_gfx: &'a OamManaged) {
-        let sprite = GRAPHICS.get("note_done").sprite(0);
-        self.object.set_sprite(object_gfx.sprite(sprite));
-        self.hit = true
+    pub fn passed(&self) -> bool {
+        self.passed
+    }
+
+    pub fn set_passed(&mut self) {
+        self.passed = true
+    }
+
+    pub fn collect(&mut self) {
+        self.object.hide();
     }
+
+    pub fn missed(&self) -> bool {
+        self.missed
+    }
+
+    pub fn set_missed(&mut self) {
+        self.missed = true
+    }
+
+    pub fn set_hit(&mut self) {
+        self.hit = true;
+
+        // Ghost notes reappear to show they were hit
+        self.object.show();
+    }
+}
+
+fn centre_offset(sprite: &Sprite) -> Vector2D<i32> {
+    let (width, height) = sprite.size().to_width_height();
+
+    Vector2D::new(
+        (NOTE_SIZE - width as i32) / 2,
+        (NOTE_SIZE - height as i32) / 2,
+    )
 }
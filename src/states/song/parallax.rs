@@ -0,0 +1,121 @@
+use agb::{
+    display::{
+        tile_data::TileData,
+        tiled::{
+            MapLoan, RegularBackgroundSize, RegularMap, TileFormat, Tiled0, TiledMap, VRamManager,
+        },
+        Priority,
+    },
+    fixnum::Vector2D,
+};
+
+use super::FLOOR_HEIGHT;
+
+// Theme images are 32 tiles wide, so each layer wraps seamlessly as it scrolls.
+// They're split into the rows of each layer, and where the layer is drawn
+const SKY_ROWS: u16 = 16;
+const SCENERY_ROWS: u16 = 8;
+const FLOOR_ROWS: u16 = 5;
+const MAP_WIDTH: i32 = 32 * 8;
+
+// Notes at normal speed move a pixel a frame, layers further back scroll slower
+const SCENERY_DIVISOR: i32 = 4;
+const SKY_DIVISOR: i32 = 16;
+
+pub struct Parallax<'b> {
+    floor: MapLoan<'b, RegularMap>,
+    scenery: MapLoan<'b, RegularMap>,
+    sky: MapLoan<'b, RegularMap>,
+}
+
+impl<'b> Parallax<'b> {
+    pub fn new(tiled0: &'b Tiled0<'b>, vram: &mut VRamManager, tiles: &TileData) -> Self {
+        // Backgrounds with the same priority draw in the order they're created
+        let floor = tiled0.background(
+            Priority::P2,
+            RegularBackgroundSize::Background32x32,
+            TileFormat::FourBpp,
+        );
+
+        let scenery = tiled0.background(
+            Priority::P3,
+            RegularBackgroundSize::Background32x32,
+            TileFormat::FourBpp,
+        );
+
+        let sky = tiled0.background(
+            Priority::P3,
+            RegularBackgroundSize::Background32x32,
+            TileFormat::FourBpp,
+        );
+
+        let mut parallax = Self {
+            floor,
+            scenery,
+            sky,
+        };
+
+        let floor_top = FLOOR_HEIGHT - 1;
+
+        Self::fill(&mut parallax.sky, vram, tiles, 0, 0, SKY_ROWS);
+        Self::fill(
+            &mut parallax.scenery,
+            vram,
+            tiles,
+            SKY_ROWS,
+            floor_top - SCENERY_ROWS,
+            SCENERY_ROWS,
+        );
+        Self::fill(
+            &mut parallax.floor,
+            vram,
+            tiles,
+            SKY_ROWS + SCENERY_ROWS,
+            floor_top,
+            FLOOR_ROWS,
+        );
+
+        parallax
+    }
+
+    /// Copies `rows` rows of the theme image, starting at `from`, to the map at `to`
+    fn fill(
+        map: &mut MapLoan<'b, RegularMap>,
+        vram: &mut VRamManager,
+        tiles: &TileData,
+        from: u16,
+        to: u16,
+        rows: u16,
+    ) {
+        for y in 0..rows {
+            for x in 0..32u16 {
+                map.set_tile(
+                    vram,
+                    (x, to + y).into(),
+                    &tiles.tiles,
+                    tiles.tile_settings[((from + y) * 32 + x) as usize],
+                );
+            }
+        }
+    }
+
+    pub fn show(&mut self) {
+        self.floor.show();
+        self.scenery.show();
+        self.sky.show();
+    }
+
+    /// Scrolls each layer for the given frame, offset by any screen shake
+    pub fn update(&mut self, vram: &mut VRamManager, frame: usize, shake: Vector2D<i16>) {
+        let floor = frame as i32 % (MAP_WIDTH * SKY_DIVISOR);
+
+        for (map, x) in [
+            (&mut self.floor, floor),
+            (&mut self.scenery, floor / SCENERY_DIVISOR),
+            (&mut self.sky, floor / SKY_DIVISOR),
+        ] {
+            map.set_scroll_pos(Vector2D::new((x % MAP_WIDTH) as i16, 0) + shake);
+            map.commit(vram);
+        }
+    }
+}
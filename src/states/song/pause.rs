@@ -1,13 +1,14 @@
 use agb::{
     display::{
         object::{OamManaged, Object},
+        tile_data::TileData,
         tiled::{MapLoan, RegularMap, VRamManager},
     },
     fixnum::Vector2D,
     sound::mixer::{ChannelId, Mixer},
 };
 
-use super::{background, GRAPHICS};
+use super::GRAPHICS;
 
 const PAUSE_TOP: u16 = 6;
 const PAUSE_IMAGE_TOP: u16 = PAUSE_TOP + 1;
@@ -67,19 +68,20 @@ impl<'a> Pause<'a> {
         self.object.is_visible()
     }
 
-    pub fn pause(&mut self, mixer: &mut Mixer, channel_id: &ChannelId) {
+    /// The channel is None before the music starts
+    pub fn pause(&mut self, mixer: &mut Mixer, channel_id: Option<&ChannelId>) {
         self.object.set_position(self.menu_pos());
         self.object.show();
 
-        if let Some(channel) = mixer.channel(channel_id) {
+        if let Some(channel) = channel_id.and_then(|channel_id| mixer.channel(channel_id)) {
             channel.pause();
         }
     }
 
-    pub fn resume(&mut self, mixer: &mut Mixer, channel_id: &ChannelId) {
+    pub fn resume(&mut self, mixer: &mut Mixer, channel_id: Option<&ChannelId>) {
         self.object.hide();
 
-        if let Some(channel) = mixer.channel(channel_id) {
+        if let Some(channel) = channel_id.and_then(|channel_id| mixer.channel(channel_id)) {
             channel.resume();
         }
     }
@@ -107,6 +109,7 @@ impl<'a> Pause<'a> {
         map: &mut MapLoan<RegularMap>,
         vram: &mut VRamManager,
         object_gfx: &'a OamManaged,
+        tiles: &TileData,
     ) {
         if self.frame == 80 {
             self.frame = 0;
@@ -151,11 +154,11 @@ impl<'a> Pause<'a> {
                     }
                 };
 
-                let tile_settings = background::tiles.tile_settings[tile_id]
+                let tile_settings = tiles.tile_settings[tile_id]
                     .hflip(x == PAUSE_RIGHT)
                     .vflip(y == PAUSE_BOTTOM);
 
-                map.set_tile(vram, (x, y).into(), &background::tiles.tiles, tile_settings);
+                map.set_tile(vram, (x, y).into(), &tiles.tiles, tile_settings);
             }
         }
     }
@@ -1,21 +1,21 @@
 use agb::display::object::{OamManaged, Object};
 
-use super::GRAPHICS;
+use crate::{
+    character::{Animation, Character},
+    song_data::Track,
+};
 
-pub enum Animation {
-    Running,
-    AttackLow,
-    AttackHigh,
-}
+use super::GRAPHICS;
 
-struct AnimationState {
-    current: Animation,
-    frame: usize,
+/// The player is on the low track unless they've jumped to the high track
+enum AnimationState {
+    Ground { current: Animation, frame: usize },
+    Air { frame: usize }, // Attacks as the jump starts, then holds the pose
 }
 
 impl Default for AnimationState {
     fn default() -> Self {
-        Self {
+        Self::Ground {
             current: Animation::Running,
             frame: 0,
         }
@@ -24,35 +24,49 @@ impl Default for AnimationState {
 
 const ATTACK_LOW_LENGTH: usize = 5;
 const ATTACK_HIGH_LENGTH: usize = 4;
+// Updates spent on the high track after jumping, separate from the attack so
+// obstacles can be tuned without changing the animation
+const AIR_LENGTH: usize = 4;
 impl AnimationState {
     fn animation(&self) -> &Animation {
-        &self.current
+        match self {
+            Self::Ground { current, .. } => current,
+            Self::Air { .. } => &Animation::AttackHigh,
+        }
     }
 
     fn frame(&self) -> usize {
-        self.frame
+        match self {
+            Self::Ground { frame, .. } => *frame,
+            Self::Air { frame } => (*frame).min(ATTACK_HIGH_LENGTH - 1),
+        }
+    }
+
+    fn in_air(&self) -> bool {
+        matches!(self, Self::Air { .. })
     }
 
     fn change_to(&mut self, anim: Animation) {
-        self.current = anim;
-        self.frame = 0;
+        *self = match anim {
+            Animation::AttackHigh => Self::Air { frame: 0 },
+            current => Self::Ground { current, frame: 0 },
+        };
     }
 
     fn update(&mut self) {
-        self.frame += 1;
-
-        match self.current {
-            Animation::AttackLow => {
-                if self.frame == ATTACK_LOW_LENGTH {
-                    self.change_to(Animation::Running)
-                }
+        let finished = match self {
+            Self::Ground { current, frame } => {
+                *frame += 1;
+                matches!(current, Animation::AttackLow) && *frame == ATTACK_LOW_LENGTH
             }
-            Animation::AttackHigh => {
-                if self.frame == ATTACK_HIGH_LENGTH {
-                    self.change_to(Animation::Running)
-                }
+            Self::Air { frame } => {
+                *frame += 1;
+                *frame == AIR_LENGTH
             }
-            _ => (),
+        };
+
+        if finished {
+            self.change_to(Animation::Running)
         }
     }
 }
@@ -60,6 +74,8 @@ impl AnimationState {
 pub struct Player<'a> {
     object: Object<'a>,
     animation_state: AnimationState,
+    character: Character,
+    fever: bool,
 }
 
 impl<'a> Player<'a> {
@@ -72,16 +88,18 @@ impl<'a> Player<'a> {
         Self {
             object,
             animation_state: AnimationState::default(),
+            character: Character::default(),
+            fever: false,
         }
     }
 
     pub fn draw(&mut self, object_gfx: &OamManaged) {
-        let sprite = match self.animation_state.animation() {
-            Animation::Running => GRAPHICS.get("player_run"),
-            Animation::AttackLow => GRAPHICS.get("player_attack_low"),
-            Animation::AttackHigh => GRAPHICS.get("player_attack_high"),
-        }
-        .animation_sprite(self.animation_state.frame());
+        let tag = self
+            .character
+            .tag(self.animation_state.animation(), self.fever);
+        let sprite = GRAPHICS
+            .get(tag)
+            .animation_sprite(self.animation_state.frame());
 
         self.object.set_sprite(object_gfx.sprite(sprite));
     }
@@ -90,6 +108,22 @@ impl<'a> Player<'a> {
         self.animation_state.update()
     }
 
+    pub fn track(&self) -> Track {
+        if self.animation_state.in_air() {
+            Track::High
+        } else {
+            Track::Low
+        }
+    }
+
+    pub fn set_character(&mut self, character: Character) {
+        self.character = character;
+    }
+
+    pub fn set_fever(&mut self, fever: bool) {
+        self.fever = fever;
+    }
+
     pub fn set_animation(&mut self, anim: Animation) {
         self.animation_state.change_to(anim)
     }
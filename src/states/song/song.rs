@@ -1,105 +1,261 @@
 use agb::{
     display::object::OamManaged,
+    fixnum::Vector2D,
     input::{Button, ButtonController},
+    sound::mixer::{Mixer, SoundChannel},
 };
 use alloc::vec::Vec;
 
 use crate::{
-    score::Score,
+    character::Character,
+    score::{Judgement, Judgements, Score},
+    sfx::{self, Sfx},
     song_data::{Command, Track},
     songs::SongID,
 };
 
-use super::{note::Note, JUDGEMENT_AREA};
+use super::{
+    boss::Boss,
+    effects::Effects,
+    note::{Note, NoteKind},
+    JUDGEMENT_AREA,
+};
+
+// Location a note is at when hit perfectly, and how far off still counts
+pub const PERFECT_LOCATION: i32 = JUDGEMENT_AREA as i32 * 8 + 6;
+const PERFECT_WINDOW: i32 = 2;
+const GREAT_WINDOW: i32 = 5;
+
+pub const MAX_HP: usize = 100;
+const MISS_DAMAGE: usize = 10;
+const HIT_HEAL: usize = 1;
+const OBSTACLE_DAMAGE: usize = 20;
+const HEART_HEAL: usize = 20;
+const MUSIC_NOTE_SCORE: usize = 200;
+
+// Hits needed to fill the fever gauge, and how long fever lasts in frames
+pub const FEVER_MAX: usize = 40;
+const FEVER_LENGTH: usize = 60 * 5;
 
 pub enum SongResult {
     None,
     UpdateText,
     Finished,
+    Failed,
 }
 
 pub struct Song<'a> {
     song_id: SongID,
     notes: Vec<Note<'a>>,
-    current_speed: i32,
+    boss: Boss<'a>,
+    effects: Effects<'a>,
     index: usize,
 
     score: usize,
     combo: usize,
     max_combo: usize,
-    hit: usize,
+    judgements: Judgements,
+    hp: usize,
+    max_hp: usize,
+    character: Character,
+    no_fail: bool,
+    sfx_volume: u8,
+    fever_gauge: usize,
+    fever_frames: usize, // Remaining while fever is active
 }
 
 impl<'a> Song<'a> {
-    pub fn new(song_id: SongID) -> Self {
+    pub fn new(song_id: SongID, object_gfx: &'a OamManaged) -> Self {
         Self {
             song_id,
             notes: Vec::new(),
-            current_speed: 1,
+            boss: Boss::new(object_gfx),
+            effects: Effects::new(),
             index: 0,
 
             score: 0,
             combo: 0,
             max_combo: 0,
-            hit: 0,
+            judgements: Judgements::default(),
+            hp: MAX_HP,
+            max_hp: MAX_HP,
+            character: Character::default(),
+            no_fail: false,
+            sfx_volume: 0,
+            fever_gauge: 0,
+            fever_frames: 0,
         }
     }
 
+    pub fn set_no_fail(&mut self, no_fail: bool) {
+        self.no_fail = no_fail;
+    }
+
+    /// Applies the character's skill, call before the song starts
+    pub fn set_character(&mut self, character: Character) {
+        self.character = character;
+        self.max_hp = character.skill().max_hp(MAX_HP);
+        self.hp = self.max_hp;
+    }
+
+    pub fn set_sfx_volume(&mut self, sfx_volume: u8) {
+        self.sfx_volume = sfx_volume;
+    }
+
     pub fn update(
         &mut self,
         object_gfx: &'a OamManaged,
+        mixer: &mut Mixer,
         input: &ButtonController,
         frame: usize,
+        player_track: Track,
     ) -> SongResult {
-        // Check for new notes
-        if self.index < self.song_id.fragments().len() {
+        // Check for new notes, notes with different speeds can appear on the same frame
+        while self.index < self.song_id.fragments().len() {
             let fragment = &self.song_id.fragments()[self.index];
 
-            if fragment.frame() == frame {
-                self.index += 1;
+            if fragment.frame() > frame {
+                break;
+            }
 
-                match fragment.command() {
-                    Command::Note(track) => self.notes.push(Note::new(object_gfx, *track)),
-                    Command::NoteBoth => {
-                        self.notes.push(Note::new(object_gfx, Track::Low));
-                        self.notes.push(Note::new(object_gfx, Track::High))
-                    }
-                    Command::SetSpeed(speed) => self.current_speed = *speed,
+            self.index += 1;
+
+            let motion = fragment.motion();
+            let skin = fragment.skin();
+            let sample = fragment.sample();
+            let new_note = |kind, track| Note::new(object_gfx, kind, track, motion);
+            let new_hittable = |track| {
+                new_note(NoteKind::Note, track)
+                    .skin(object_gfx, skin)
+                    .keysound(sample)
+            };
+
+            match fragment.command() {
+                Command::Note(track) => self.notes.push(new_hittable(*track)),
+                Command::NoteBoth => {
+                    self.notes.push(new_hittable(Track::Low));
+                    self.notes.push(new_hittable(Track::High))
                 }
+                Command::Ghost(track) => self.notes.push(new_hittable(*track).ghost(object_gfx)),
+                Command::GhostBoth => {
+                    self.notes.push(new_hittable(Track::Low).ghost(object_gfx));
+                    self.notes.push(new_hittable(Track::High).ghost(object_gfx))
+                }
+                Command::BossEnter => self.boss.enter(),
+                Command::BossExit => self.boss.exit(),
+                Command::Throw(track) => {
+                    self.boss.throw();
+                    self.notes
+                        .push(new_hittable(*track).thrown(self.boss.hand()))
+                }
+                Command::Obstacle => self.notes.push(new_note(NoteKind::Obstacle, Track::Low)),
+                Command::Heart(track) => self.notes.push(new_note(NoteKind::Heart, *track)),
+                Command::MusicNote(track) => self.notes.push(new_note(NoteKind::MusicNote, *track)),
             }
-        } else if self.notes.is_empty() {
+        }
+
+        if self.index >= self.song_id.fragments().len() && self.notes.is_empty() {
             return SongResult::Finished;
         }
 
+        self.boss.update(object_gfx, frame);
+
         let mut remove = None;
         let mut result = SongResult::None;
+        let fever = self.fever_active();
+        let skill = self.character.skill();
+        let great_window = skill.great_window(GREAT_WINDOW);
+
+        if self.fever_frames > 0 {
+            self.fever_frames -= 1;
+
+            if self.fever_frames == 0 {
+                result = SongResult::UpdateText;
+            }
+        }
         for (i, note) in self.notes.iter_mut().enumerate() {
-            note.update(self.current_speed);
+            note.update();
+
+            if frame % 8 == 0 {
+                note.animate(object_gfx, frame / 8);
+            }
 
             if note.location() < -10 {
                 // Check if note should be deleted
                 remove = Some(i);
-            } else if note.location() > JUDGEMENT_AREA as i32 * 8
-                && note.location() < JUDGEMENT_AREA as i32 * 8 + 12
-            {
+            } else if note.kind() != NoteKind::Note {
+                // Obstacles and pickups affect the player if they're on the same track
+                if !note.passed() && note.location() < PERFECT_LOCATION {
+                    note.set_passed();
+
+                    let collects_heart = note.kind() == NoteKind::Heart && skill.collects_hearts();
+
+                    if player_track == *note.track() || collects_heart {
+                        match note.kind() {
+                            NoteKind::Obstacle => {
+                                self.hp = self.hp.saturating_sub(OBSTACLE_DAMAGE);
+                                self.effects.shake();
+                                sfx::play(mixer, Sfx::Miss, self.sfx_volume);
+                            }
+                            NoteKind::Heart => {
+                                note.collect();
+                                self.hp = (self.hp + HEART_HEAL).min(self.max_hp);
+                            }
+                            NoteKind::MusicNote => {
+                                note.collect();
+                                self.score += MUSIC_NOTE_SCORE;
+                            }
+                            NoteKind::Note => unreachable!(),
+                        }
+
+                        result = SongResult::UpdateText;
+                    }
+                }
+            } else if (note.location() - PERFECT_LOCATION).abs() <= great_window {
                 // Check for notes being hit
                 let button = match note.track() {
                     Track::Low => Button::R,
                     Track::High => Button::L,
                 };
 
-                if input.is_just_pressed(button) {
-                    note.set_hit(object_gfx);
-                    self.hit += 1;
+                if !note.hit() && input.is_just_pressed(button) {
+                    let offset = note.location() - PERFECT_LOCATION;
+                    let judgement = if offset.abs() <= PERFECT_WINDOW {
+                        Judgement::Perfect
+                    } else {
+                        Judgement::Great
+                    };
+
+                    note.set_hit();
+                    self.judgements.add_hit(*note.track(), judgement, offset);
+                    self.effects.hit(object_gfx, judgement, *note.track());
+                    // Keysounds are part of the music, so aren't affected by the effects volume
+                    if let Some(sample) = note.sample() {
+                        mixer.play_sound(SoundChannel::new(sample));
+                    } else {
+                        sfx::play(mixer, Sfx::Hit(judgement, *note.track()), self.sfx_volume);
+                    }
                     self.combo += 1;
-                    self.score += calc_score(self.combo);
-                    result = SongResult::UpdateText;
-                }
-            } else if !note.hit() && note.location() < JUDGEMENT_AREA as i32 * 8 {
-                // Only redraw if needed (fixes slowdown)
-                if self.combo >= 5 {
+                    self.score += skill.hit_score(calc_score(self.combo, fever));
+
+                    // The gauge empties while fever is active, so it doesn't fill
+                    if !fever {
+                        self.fever_gauge = (self.fever_gauge + 1).min(FEVER_MAX);
+                    }
+
+                    self.hp = (self.hp + HIT_HEAL).min(self.max_hp);
                     result = SongResult::UpdateText;
                 }
+            } else if !note.hit()
+                && !note.missed()
+                && note.location() < PERFECT_LOCATION - great_window
+            {
+                note.set_missed();
+                self.judgements.add_miss(*note.track());
+                self.effects.miss(object_gfx, *note.track());
+                self.hp = self.hp.saturating_sub(MISS_DAMAGE);
+                sfx::play(mixer, Sfx::Miss, self.sfx_volume);
+                result = SongResult::UpdateText;
 
                 if self.combo > self.max_combo {
                     self.max_combo = self.combo;
@@ -108,16 +264,27 @@ impl<'a> Song<'a> {
                 self.combo = 0;
             }
 
-            note.draw();
+            note.draw(object_gfx);
         }
 
         if let Some(index) = remove {
             self.notes.remove(index);
         }
 
+        self.effects.update(object_gfx);
+
+        if self.failed() {
+            return SongResult::Failed;
+        }
+
         result
     }
 
+    /// How far to offset the background to shake the screen
+    pub fn shake_offset(&self) -> Vector2D<i16> {
+        self.effects.shake_offset()
+    }
+
     pub fn score(&self) -> usize {
         self.score
     }
@@ -126,8 +293,58 @@ impl<'a> Song<'a> {
         self.combo
     }
 
+    pub fn hp(&self) -> usize {
+        self.hp
+    }
+
+    pub fn max_hp(&self) -> usize {
+        self.max_hp
+    }
+
+    pub fn fever_gauge(&self) -> usize {
+        self.fever_gauge
+    }
+
+    pub fn fever_ready(&self) -> bool {
+        self.fever_gauge == FEVER_MAX
+    }
+
+    pub fn fever_active(&self) -> bool {
+        self.fever_frames > 0
+    }
+
+    /// Starts fever if the gauge is full, returning whether it started
+    pub fn activate_fever(&mut self) -> bool {
+        if !self.fever_ready() {
+            return false;
+        }
+
+        self.fever_gauge = 0;
+        self.fever_frames = FEVER_LENGTH;
+
+        true
+    }
+
+    pub fn failed(&self) -> bool {
+        self.hp == 0 && !self.no_fail
+    }
+
+    pub fn full_combo(&self) -> bool {
+        self.judgements.miss() == 0
+    }
+
+    pub fn all_perfect(&self) -> bool {
+        self.full_combo() && self.judgements.great() == 0
+    }
+
     pub fn final_score(&self) -> Score {
-        let accuracy = (self.hit * 100) / self.song_id.fragments().len();
+        let notes = self.song_id.notes();
+
+        let accuracy = if notes == 0 {
+            100
+        } else {
+            ((self.judgements.hit() * 100) / notes).min(100)
+        };
 
         let max_combo = if self.combo > self.max_combo {
             self.combo
@@ -135,11 +352,18 @@ impl<'a> Song<'a> {
             self.max_combo
         };
 
-        Score::new(self.score, max_combo, accuracy as u8)
+        Score::new(
+            self.score,
+            max_combo,
+            self.judgements,
+            accuracy as u8,
+            !self.failed(),
+            self.character,
+        )
     }
 }
 
-fn calc_score(combo: usize) -> usize {
+fn calc_score(combo: usize, fever: bool) -> usize {
     let multiplier = match combo {
         0..=9 => 100,
         10..=19 => 110,
@@ -149,5 +373,7 @@ fn calc_score(combo: usize) -> usize {
         _ => 150,
     };
 
-    multiplier // TODO: different note types
+    let fever_multiplier = if fever { 2 } else { 1 };
+
+    multiplier * fever_multiplier // TODO: different note types
 }
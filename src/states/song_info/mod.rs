@@ -4,7 +4,7 @@ use agb::{
     display::{
         object::{OamManaged, Object},
         tiled::{
-            MapLoan, RegularBackgroundSize, RegularMap, TileFormat, Tiled1, TiledMap, VRamManager,
+            MapLoan, RegularBackgroundSize, RegularMap, TileFormat, Tiled0, TiledMap, VRamManager,
         },
         Priority,
     },
@@ -13,12 +13,21 @@ use agb::{
     sound::mixer::Mixer,
 };
 
-use crate::{save_data::SaveDataManager, score::Grade, songs::SongID, FONT};
+use crate::{
+    save_data::SaveDataManager,
+    score::Grade,
+    sfx::{self, Sfx},
+    songs::SongID,
+    FONT,
+};
 
 use super::{Callback, State};
 
 include_background_gfx!(background, tiles => "assets/result_tiles.aseprite");
 
+// Tile row the stats start on, below the longest score list
+const STATS_ROW: u16 = 12;
+
 //const GRAPHICS: &TagMap = include_aseprite!("assets/grades.aseprite").tags();
 
 pub struct SongInfoState<'a, 'b> {
@@ -48,20 +57,20 @@ impl<'a, 'b> State<'a, 'b> for SongInfoState<'a, 'b> {
         &mut self,
         save_data: &mut SaveDataManager,
         _object_gfx: &'a OamManaged,
-        tiled1: &'b Tiled1<'b>,
+        tiled0: &'b Tiled0<'b>,
         vram: &mut VRamManager,
         _mixer: &mut Mixer,
     ) {
         // Background
         vram.set_background_palettes(background::PALETTES);
 
-        let mut bg = tiled1.regular(
+        let mut bg = tiled0.background(
             Priority::P3,
             RegularBackgroundSize::Background32x32,
             TileFormat::FourBpp,
         );
 
-        let mut text = tiled1.regular(
+        let mut text = tiled0.background(
             Priority::P1,
             RegularBackgroundSize::Background32x32,
             TileFormat::FourBpp,
@@ -114,6 +123,45 @@ impl<'a, 'b> State<'a, 'b> for SongInfoState<'a, 'b> {
             let mut writer = renderer.writer(10, 0, &mut text, vram);
             write!(writer, " - {}", score.score()).unwrap();
             writer.commit();
+
+            let mut writer = renderer.writer(3, 0, &mut text, vram);
+            if let Some(date) = score.date() {
+                write!(writer, "  {}", date).unwrap();
+            } else {
+                write!(writer, "  #{}", score.play()).unwrap();
+            }
+            writer.commit();
+        }
+
+        let stats = save_data.get_stats(self.song_id);
+
+        // Stats go on their own rows below the score list, so long score rows can't overlap them
+        let mut stats_renderer = FONT.render_text((0u16, STATS_ROW).into());
+        {
+            let mut writer = stats_renderer.writer(10, 0, &mut text, vram);
+
+            write!(
+                writer,
+                " Plays: {}  Clears: {}\n Best acc: {}%  Combo: {}\n Notes hit: {}",
+                stats.plays(),
+                stats.clears(),
+                stats.best_accuracy(),
+                stats.best_max_combo(),
+                stats.notes_hit()
+            )
+            .unwrap();
+
+            writer.commit();
+        }
+
+        if stats.all_perfect() {
+            let mut writer = stats_renderer.writer(9, 0, &mut text, vram);
+            write!(writer, "\n ALL PERFECT").unwrap();
+            writer.commit();
+        } else if stats.full_combo() {
+            let mut writer = stats_renderer.writer(8, 0, &mut text, vram);
+            write!(writer, "\n FULL COMBO").unwrap();
+            writer.commit();
         }
 
         text.commit(vram);
@@ -124,15 +172,19 @@ impl<'a, 'b> State<'a, 'b> for SongInfoState<'a, 'b> {
 
     fn update(
         &mut self,
-        _save_data: &mut SaveDataManager,
+        save_data: &mut SaveDataManager,
         _object_gfx: &'a OamManaged,
         _vram: &mut VRamManager,
-        _mixer: &mut Mixer,
+        mixer: &mut Mixer,
         input: &ButtonController,
     ) -> Callback {
+        let volume = save_data.get_settings().sfx_volume();
+
         if input.is_just_pressed(Button::A) || input.is_just_pressed(Button::START) {
+            sfx::play(mixer, Sfx::MenuSelect, volume);
             Callback::SetState(super::SetState::Song(self.song_id))
         } else if input.is_just_pressed(Button::B) {
+            sfx::play(mixer, Sfx::MenuBack, volume);
             Callback::SetState(super::SetState::SongMenu)
         } else {
             Callback::None
@@ -5,7 +5,7 @@ use agb::{
         font::TextRenderer,
         object::{OamManaged, Object, TagMap},
         tiled::{
-            MapLoan, RegularBackgroundSize, RegularMap, TileFormat, Tiled1, TiledMap, VRamManager,
+            MapLoan, RegularBackgroundSize, RegularMap, TileFormat, Tiled0, TiledMap, VRamManager,
         },
         Priority,
     },
@@ -16,6 +16,7 @@ use agb::{
 
 use crate::{
     save_data::SaveDataManager,
+    sfx::{self, Sfx},
     songs::{self, SongID},
     FONT,
 };
@@ -52,20 +53,38 @@ impl<'a, 'b> SongMenuState<'a, 'b> {
         }
     }
 
-    pub fn redraw_songs(&mut self, vram: &mut VRamManager) {
+    pub fn redraw_songs(&mut self, save_data: &mut SaveDataManager, vram: &mut VRamManager) {
         if let Some((text, renderer)) = &mut self.text {
             text.clear(vram);
             renderer.clear(vram);
 
             let mut writer = renderer.writer(3, 0, text, vram);
+            write!(writer, "Select song:",).unwrap();
+            writer.commit();
 
-            writeln!(writer, "Select song:",).unwrap();
-            for song in songs::SONGS.iter().skip(self.menu_offset).take(MAX_SONGS) {
-                writeln!(writer, "{}", song.name()).unwrap();
+            for (i, song) in songs::SONGS
+                .iter()
+                .enumerate()
+                .skip(self.menu_offset)
+                .take(MAX_SONGS)
+            {
+                let mut writer = renderer.writer(3, 0, text, vram);
+                write!(writer, "\n{}", song.name()).unwrap();
+                writer.commit();
+
+                let stats = save_data.get_stats(SongID::new(i));
+
+                if stats.all_perfect() {
+                    let mut writer = renderer.writer(9, 0, text, vram);
+                    write!(writer, " AP").unwrap();
+                    writer.commit();
+                } else if stats.full_combo() {
+                    let mut writer = renderer.writer(8, 0, text, vram);
+                    write!(writer, " FC").unwrap();
+                    writer.commit();
+                }
             }
 
-            writer.commit();
-
             text.commit(vram);
             text.show();
         }
@@ -75,22 +94,22 @@ impl<'a, 'b> SongMenuState<'a, 'b> {
 impl<'a, 'b> State<'a, 'b> for SongMenuState<'a, 'b> {
     fn init(
         &mut self,
-        _save_data: &mut SaveDataManager,
+        save_data: &mut SaveDataManager,
         _object_gfx: &'a OamManaged,
-        tiled1: &'b Tiled1<'b>,
+        tiled0: &'b Tiled0<'b>,
         vram: &mut VRamManager,
         _mixer: &mut Mixer,
     ) {
         // Background
         vram.set_background_palettes(background::PALETTES);
 
-        let mut bg = tiled1.regular(
+        let mut bg = tiled0.background(
             Priority::P3,
             RegularBackgroundSize::Background32x32,
             TileFormat::FourBpp,
         );
 
-        let text = tiled1.regular(
+        let text = tiled0.background(
             Priority::P1,
             RegularBackgroundSize::Background32x32,
             TileFormat::FourBpp,
@@ -124,31 +143,35 @@ impl<'a, 'b> State<'a, 'b> for SongMenuState<'a, 'b> {
 
         self.text = Some((text, renderer));
 
-        self.redraw_songs(vram);
+        self.redraw_songs(save_data, vram);
     }
 
     fn update(
         &mut self,
-        _save_data: &mut SaveDataManager,
+        save_data: &mut SaveDataManager,
         _object_gfx: &'a OamManaged,
         vram: &mut VRamManager,
-        _mixer: &mut Mixer,
+        mixer: &mut Mixer,
         input: &ButtonController,
     ) -> Callback {
+        let volume = save_data.get_settings().sfx_volume();
+
         if input.is_just_pressed(Button::UP) && self.current_option > 0 {
             self.current_option -= 1;
+            sfx::play(mixer, Sfx::MenuMove, volume);
         }
 
         if input.is_just_pressed(Button::DOWN) && self.current_option < songs::SONGS.len() - 1 {
             self.current_option += 1;
+            sfx::play(mixer, Sfx::MenuMove, volume);
         }
 
         if self.current_option >= self.menu_offset + MAX_SONGS {
             self.menu_offset += 1;
-            self.redraw_songs(vram);
+            self.redraw_songs(save_data, vram);
         } else if self.current_option < self.menu_offset {
             self.menu_offset -= 1;
-            self.redraw_songs(vram);
+            self.redraw_songs(save_data, vram);
         }
 
         let y = ((self.current_option - self.menu_offset + 1) * 14) - 1;
@@ -163,8 +186,10 @@ impl<'a, 'b> State<'a, 'b> for SongMenuState<'a, 'b> {
         }
 
         if input.is_just_pressed(Button::A) || input.is_just_pressed(Button::START) {
+            sfx::play(mixer, Sfx::MenuSelect, volume);
             Callback::SetState(super::SetState::SongInfo(SongID::new(self.current_option)))
         } else if input.is_just_pressed(Button::B) {
+            sfx::play(mixer, Sfx::MenuBack, volume);
             Callback::SetState(super::SetState::MainMenu)
         } else {
             Callback::None
@@ -0,0 +1,173 @@
+use crate::{
+    character::Character,
+    score::{Grade, Score},
+};
+
+pub const NAME_LENGTH: usize = 8;
+
+#[derive(Debug, Default, Clone, Copy, Hash)]
+pub struct SongStats {
+    plays: u16,
+    clears: u16,
+    full_combo: bool,
+    all_perfect: bool,
+    best_accuracy: u8,
+    best_max_combo: u16,
+    notes_hit: u32,
+}
+
+impl SongStats {
+    pub const PACKED_SIZE: usize = 12;
+
+    /// Counts saturate rather than wrap, the flags share a u16 with the best accuracy
+    pub fn pack(&self) -> [u8; Self::PACKED_SIZE] {
+        let flags = self.best_accuracy as u16
+            | (self.full_combo as u16) << 7
+            | (self.all_perfect as u16) << 8;
+
+        let mut bytes = [0; Self::PACKED_SIZE];
+        bytes[0..2].copy_from_slice(&self.plays.to_le_bytes());
+        bytes[2..4].copy_from_slice(&self.clears.to_le_bytes());
+        bytes[4..8].copy_from_slice(&self.notes_hit.to_le_bytes());
+        bytes[8..10].copy_from_slice(&self.best_max_combo.to_le_bytes());
+        bytes[10..12].copy_from_slice(&flags.to_le_bytes());
+        bytes
+    }
+
+    pub fn unpack(bytes: [u8; Self::PACKED_SIZE]) -> Option<Self> {
+        let flags = u16::from_le_bytes([bytes[10], bytes[11]]);
+        let best_accuracy = (flags & 0x7F) as u8;
+
+        if best_accuracy > 100 || flags >> 9 != 0 {
+            return None;
+        }
+
+        Some(Self {
+            plays: u16::from_le_bytes([bytes[0], bytes[1]]),
+            clears: u16::from_le_bytes([bytes[2], bytes[3]]),
+            notes_hit: u32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]),
+            best_max_combo: u16::from_le_bytes([bytes[8], bytes[9]]),
+            full_combo: flags & 1 << 7 != 0,
+            all_perfect: flags & 1 << 8 != 0,
+            best_accuracy,
+        })
+    }
+
+    pub fn add_result(&mut self, score: &Score) {
+        self.plays = self.plays.saturating_add(1);
+        self.notes_hit = self.notes_hit.saturating_add(score.hit() as u32);
+
+        // Bests only count cleared runs
+        if !score.cleared() {
+            return;
+        }
+
+        self.clears = self.clears.saturating_add(1);
+        self.full_combo |= score.full_combo();
+        self.all_perfect |= score.all_perfect();
+        self.best_accuracy = self.best_accuracy.max(score.accuracy());
+        let max_combo = score.max_combo().min(u16::MAX as usize) as u16;
+        self.best_max_combo = self.best_max_combo.max(max_combo);
+    }
+
+    pub fn plays(&self) -> usize {
+        self.plays as usize
+    }
+
+    pub fn clears(&self) -> usize {
+        self.clears as usize
+    }
+
+    pub fn full_combo(&self) -> bool {
+        self.full_combo
+    }
+
+    pub fn all_perfect(&self) -> bool {
+        self.all_perfect
+    }
+
+    pub fn best_grade(&self) -> Option<Grade> {
+        if self.clears > 0 {
+            Some(Grade::from_accuracy(self.best_accuracy))
+        } else {
+            None
+        }
+    }
+
+    pub fn best_accuracy(&self) -> u8 {
+        self.best_accuracy
+    }
+
+    pub fn best_max_combo(&self) -> usize {
+        self.best_max_combo as usize
+    }
+
+    pub fn notes_hit(&self) -> usize {
+        self.notes_hit as usize
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, Hash)]
+pub struct Profile {
+    name: [u8; NAME_LENGTH], // ASCII, padded with zeros
+    play_time: u32,          // In frames
+    character: Character,
+}
+
+impl Profile {
+    pub const PACKED_SIZE: usize = NAME_LENGTH + 5;
+
+    pub fn pack(&self) -> [u8; Self::PACKED_SIZE] {
+        let mut bytes = [0; Self::PACKED_SIZE];
+        bytes[..NAME_LENGTH].copy_from_slice(&self.name);
+        bytes[NAME_LENGTH..NAME_LENGTH + 4].copy_from_slice(&self.play_time.to_le_bytes());
+        bytes[NAME_LENGTH + 4] = self.character.to_u8();
+        bytes
+    }
+
+    pub fn unpack(bytes: [u8; Self::PACKED_SIZE]) -> Option<Self> {
+        let mut name = [0; NAME_LENGTH];
+        name.copy_from_slice(&bytes[..NAME_LENGTH]);
+
+        let mut play_time = [0; 4];
+        play_time.copy_from_slice(&bytes[NAME_LENGTH..NAME_LENGTH + 4]);
+
+        Some(Self {
+            name,
+            play_time: u32::from_le_bytes(play_time),
+            character: Character::try_from(bytes[NAME_LENGTH + 4]).ok()?,
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        let len = self
+            .name
+            .iter()
+            .position(|c| *c == 0)
+            .unwrap_or(NAME_LENGTH);
+        core::str::from_utf8(&self.name[..len]).unwrap_or("")
+    }
+
+    pub fn set_name(&mut self, name: &[u8]) {
+        self.name = [0; NAME_LENGTH];
+
+        let len = name.len().min(NAME_LENGTH);
+        self.name[..len].copy_from_slice(&name[..len]);
+    }
+
+    pub fn play_time(&self) -> usize {
+        self.play_time as usize
+    }
+
+    pub fn add_play_time(&mut self, frames: usize) {
+        self.play_time = self.play_time.saturating_add(frames as u32);
+    }
+
+    pub fn character(&self) -> Character {
+        self.character
+    }
+
+    pub fn set_character(&mut self, character: Character) {
+        self.character = character;
+    }
+}